@@ -0,0 +1,31 @@
+// generates the header for the functions in lib.rs into OUT_DIR, the committed include/unistroke.h is only
+// rewritten with UNISTROKE_UPDATE_HEADER=1 set, so builds leave the checkout alone (and warn when it's out of date)
+
+use std::fs;
+
+fn main() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir)).expect("cbindgen.toml is readable");
+    let generated = format!("{}/unistroke.h", out_dir);
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("the c api can be turned into a header")
+        .write_to_file(&generated);
+
+    let committed = format!("{}/include/unistroke.h", crate_dir);
+    let header = fs::read(&generated).expect("the header was just written");
+    if fs::read(&committed).ok().as_ref() != Some(&header) {
+        if std::env::var_os("UNISTROKE_UPDATE_HEADER").is_some() {
+            fs::write(&committed, &header).expect("include/unistroke.h is writable");
+        } else {
+            println!("cargo:warning=include/unistroke.h is out of date, rebuild with UNISTROKE_UPDATE_HEADER=1 to update it");
+        }
+    }
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=include/unistroke.h");
+    println!("cargo:rerun-if-env-changed=UNISTROKE_UPDATE_HEADER");
+}
@@ -0,0 +1,94 @@
+#ifndef UNISTROKE_H
+#define UNISTROKE_H
+
+/* generated by build.rs with cbindgen, don't edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum UnistrokeStatus {
+  UNISTROKE_STATUS_OK = 0,
+  UNISTROKE_STATUS_NULL_POINTER,
+  UNISTROKE_STATUS_INVALID_PACK,
+  UNISTROKE_STATUS_INVALID_NAME,
+  UNISTROKE_STATUS_TOO_SHORT,
+  /**
+   * There are no templates, or the stroke has no points.
+   */
+  UNISTROKE_STATUS_NO_MATCH,
+} UnistrokeStatus;
+
+/**
+ * A recognizer with its templates, made by unistroke_recognizer_new().
+ */
+typedef struct UnistrokeRecognizer UnistrokeRecognizer;
+
+typedef struct UnistrokePoint {
+  float x;
+  float y;
+  /**
+   * Seconds since the stroke began, 0 when unknown.
+   */
+  float t;
+} UnistrokePoint;
+
+typedef struct UnistrokeRecognition {
+  /**
+   * The closest gesture (also when it's too far away to count), owned by the recognition.
+   */
+  char *name;
+  float score;
+  bool recognized;
+} UnistrokeRecognition;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * A recognizer with the built-in gestures.
+ */
+struct UnistrokeRecognizer *unistroke_recognizer_new(void);
+
+/**
+ * Replaces the templates with a template pack (the json the app saves), the old ones stay when it's invalid.
+ */
+enum UnistrokeStatus unistroke_recognizer_load_pack(struct UnistrokeRecognizer *recognizer,
+                                                    const uint8_t *bytes,
+                                                    size_t length);
+
+/**
+ * Adds a raw stroke as a template of the gesture called name (utf-8, nul terminated).
+ */
+enum UnistrokeStatus unistroke_recognizer_add_template(struct UnistrokeRecognizer *recognizer,
+                                                       const char *name,
+                                                       const struct UnistrokePoint *points,
+                                                       size_t count);
+
+/**
+ * Recognizes a raw stroke, on UNISTROKE_STATUS_OK result has to be freed with unistroke_recognition_free().
+ */
+enum UnistrokeStatus unistroke_recognize(const struct UnistrokeRecognizer *recognizer,
+                                         const struct UnistrokePoint *points,
+                                         size_t count,
+                                         struct UnistrokeRecognition *result);
+
+/**
+ * The templates as a template pack, nul terminated json to free with unistroke_string_free().
+ */
+char *unistroke_recognizer_export_pack(const struct UnistrokeRecognizer *recognizer);
+
+void unistroke_recognition_free(struct UnistrokeRecognition *result);
+
+void unistroke_string_free(char *string);
+
+void unistroke_recognizer_free(struct UnistrokeRecognizer *recognizer);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* UNISTROKE_H */
@@ -0,0 +1,161 @@
+// the recognizer behind a c api, for linking from c and c++:
+//   UnistrokeRecognizer *recognizer = unistroke_recognizer_new();
+//   UnistrokeRecognition result;
+//   if (unistroke_recognize(recognizer, points, count, &result) == UNISTROKE_STATUS_OK) { ... }
+//   unistroke_recognition_free(&result);
+//   unistroke_recognizer_free(recognizer);
+// every function takes the pointers it was given by this library (or NULL), a NULL argument is UNISTROKE_STATUS_NULL_POINTER
+#![allow(clippy::missing_safety_doc)]
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+use std::slice;
+
+use dollar1_unistroke::engine::Recognizer;
+use dollar1_unistroke::stroke::StrokePoint;
+use glam::Vec2;
+
+/// A recognizer with its templates, made by unistroke_recognizer_new().
+pub struct UnistrokeRecognizer(Recognizer);
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct UnistrokePoint {
+    pub x: f32,
+    pub y: f32,
+    /// Seconds since the stroke began, 0 when unknown.
+    pub t: f32,
+}
+
+#[repr(C)]
+pub struct UnistrokeRecognition {
+    /// The closest gesture (also when it's too far away to count), owned by the recognition.
+    pub name: *mut c_char,
+    pub score: f32,
+    pub recognized: bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnistrokeStatus {
+    Ok = 0,
+    NullPointer,
+    InvalidPack,
+    InvalidName,
+    TooShort,
+    /// There are no templates, or the stroke has no points.
+    NoMatch,
+}
+
+unsafe fn stroke(points: *const UnistrokePoint, count: usize) -> Option<Vec<StrokePoint>> {
+    if points.is_null() {
+        return None;
+    }
+    let points = slice::from_raw_parts(points, count);
+    Some(points.iter().map(|point| StrokePoint::new(Vec2::new(point.x, point.y), point.t)).collect())
+}
+
+/// A recognizer with the built-in gestures.
+#[no_mangle]
+pub extern "C" fn unistroke_recognizer_new() -> *mut UnistrokeRecognizer {
+    Box::into_raw(Box::new(UnistrokeRecognizer(Recognizer::default())))
+}
+
+/// Replaces the templates with a template pack (the json the app saves), the old ones stay when it's invalid.
+#[no_mangle]
+pub unsafe extern "C" fn unistroke_recognizer_load_pack(
+    recognizer: *mut UnistrokeRecognizer,
+    bytes: *const u8,
+    length: usize,
+) -> UnistrokeStatus {
+    let (Some(recognizer), false) = (recognizer.as_mut(), bytes.is_null()) else {
+        return UnistrokeStatus::NullPointer;
+    };
+    let Ok(json) = std::str::from_utf8(slice::from_raw_parts(bytes, length)) else {
+        return UnistrokeStatus::InvalidPack;
+    };
+    match Recognizer::from_pack(json, recognizer.0.backend()) {
+        Ok(loaded) => {
+            recognizer.0 = loaded;
+            UnistrokeStatus::Ok
+        }
+        Err(_) => UnistrokeStatus::InvalidPack,
+    }
+}
+
+/// Adds a raw stroke as a template of the gesture called name (utf-8, nul terminated).
+#[no_mangle]
+pub unsafe extern "C" fn unistroke_recognizer_add_template(
+    recognizer: *mut UnistrokeRecognizer,
+    name: *const c_char,
+    points: *const UnistrokePoint,
+    count: usize,
+) -> UnistrokeStatus {
+    let (Some(recognizer), Some(stroke), false) = (recognizer.as_mut(), stroke(points, count), name.is_null()) else {
+        return UnistrokeStatus::NullPointer;
+    };
+    let Ok(name) = CStr::from_ptr(name).to_str() else {
+        return UnistrokeStatus::InvalidName;
+    };
+    match recognizer.0.add_template(name, &stroke) {
+        Some(_) => UnistrokeStatus::Ok,
+        None => UnistrokeStatus::TooShort,
+    }
+}
+
+/// Recognizes a raw stroke, on UNISTROKE_STATUS_OK result has to be freed with unistroke_recognition_free().
+#[no_mangle]
+pub unsafe extern "C" fn unistroke_recognize(
+    recognizer: *const UnistrokeRecognizer,
+    points: *const UnistrokePoint,
+    count: usize,
+    result: *mut UnistrokeRecognition,
+) -> UnistrokeStatus {
+    let (Some(recognizer), Some(stroke), false) = (recognizer.as_ref(), stroke(points, count), result.is_null()) else {
+        return UnistrokeStatus::NullPointer;
+    };
+    let Some(recognition) = recognizer.0.recognize(&stroke) else {
+        return UnistrokeStatus::NoMatch;
+    };
+    // gesture names come from rust strings, which can hold a nul c can't
+    let Ok(name) = CString::new(recognition.name) else {
+        return UnistrokeStatus::InvalidName;
+    };
+    result.write(UnistrokeRecognition {
+        name: name.into_raw(),
+        score: recognition.score,
+        recognized: recognition.recognized,
+    });
+    UnistrokeStatus::Ok
+}
+
+/// The templates as a template pack, nul terminated json to free with unistroke_string_free().
+#[no_mangle]
+pub unsafe extern "C" fn unistroke_recognizer_export_pack(recognizer: *const UnistrokeRecognizer) -> *mut c_char {
+    match recognizer.as_ref().map(|recognizer| CString::new(recognizer.0.export_pack())) {
+        Some(Ok(json)) => json.into_raw(),
+        _ => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn unistroke_recognition_free(result: *mut UnistrokeRecognition) {
+    if let Some(result) = result.as_mut() {
+        unistroke_string_free(result.name);
+        result.name = ptr::null_mut();
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn unistroke_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn unistroke_recognizer_free(recognizer: *mut UnistrokeRecognizer) {
+    if !recognizer.is_null() {
+        drop(Box::from_raw(recognizer));
+    }
+}
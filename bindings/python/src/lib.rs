@@ -0,0 +1,181 @@
+// the recognizer for python, to run experiments on datasets and hand tuned packs back to the app:
+//   import unistroke
+//   recognizer = unistroke.Recognizer.load("pack.json")  # or unistroke.Recognizer() for the built-in gestures
+//   for name, points in unistroke.read_strokes(open("arrow01.xml").read()):
+//       print(name, recognizer.recognize(points))
+//   recognizer.add_template("zorro", points); recognizer.save("pack.json")
+// points are sequences of (x, y) or (x, y, t) with t in seconds, lists or a numpy array with a row per point
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use dollar1_unistroke::engine;
+use dollar1_unistroke::formats::parse_strokes;
+use dollar1_unistroke::recognizer::{self, Backend};
+use dollar1_unistroke::stroke::{positions, StrokePoint};
+use glam::Vec2;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+
+type Point = (f32, f32);
+type TimedPoint = (f32, f32, f32);
+
+fn stroke(points: Vec<Vec<f32>>) -> PyResult<Vec<StrokePoint>> {
+    points
+        .into_iter()
+        .map(|point| match point[..] {
+            [x, y] => Ok(StrokePoint::new(Vec2::new(x, y), 0.0)),
+            [x, y, t] => Ok(StrokePoint::new(Vec2::new(x, y), t)),
+            _ => Err(PyValueError::new_err("points are (x, y) or (x, y, t)")),
+        })
+        .collect()
+}
+
+fn vec2s(points: Vec<Vec<f32>>) -> PyResult<Vec<Vec2>> {
+    Ok(positions(&stroke(points)?))
+}
+
+fn tuples(points: &[Vec2]) -> Vec<Point> {
+    points.iter().map(|point| (point.x, point.y)).collect()
+}
+
+fn backend(name: &str) -> PyResult<Backend> {
+    Backend::from_name(name).ok_or_else(|| PyValueError::new_err("the backend is one of dollar1, rubine or dtw"))
+}
+
+/// Length of the path through the points.
+#[pyfunction]
+fn path_length(points: Vec<Vec<f32>>) -> PyResult<f32> {
+    Ok(recognizer::path_length(&vec2s(points)?))
+}
+
+/// The points resampled to 64 evenly spaced ones along the path.
+#[pyfunction]
+fn resample(points: Vec<Vec<f32>>) -> PyResult<Vec<Point>> {
+    let points = vec2s(points)?;
+    if points.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(tuples(&recognizer::resample(recognizer::path_length(&points), &points)))
+}
+
+/// Resampled, rotated by the indicative angle, scaled and centred: what the templates are compared in.
+#[pyfunction]
+fn normalize(points: Vec<Vec<f32>>) -> PyResult<Vec<Point>> {
+    let points = vec2s(points)?;
+    if points.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(tuples(&recognizer::normalize(&points)))
+}
+
+/// Every stroke in a stroke file (json points or the $1 xml logs) as (name or None, [(x, y, t), ...]).
+#[pyfunction]
+fn read_strokes(text: &str) -> PyResult<Vec<(Option<String>, Vec<TimedPoint>)>> {
+    let strokes = parse_strokes(text).map_err(|error| PyValueError::new_err(error.to_string()))?;
+    Ok(strokes
+        .into_iter()
+        .map(|file| (file.name, file.stroke.iter().map(|point| (point.pos.x, point.pos.y, point.t)).collect()))
+        .collect())
+}
+
+#[pyclass(get_all, frozen)]
+struct Recognition {
+    /// The closest gesture, also when it's too far away to count.
+    name: String,
+    score: f32,
+    recognized: bool,
+}
+
+#[pymethods]
+impl Recognition {
+    fn __repr__(&self) -> String {
+        format!(
+            "Recognition(name={:?}, score={}, recognized={})",
+            self.name,
+            self.score,
+            if self.recognized { "True" } else { "False" }
+        )
+    }
+}
+
+#[pyclass]
+struct Recognizer(engine::Recognizer);
+
+#[pymethods]
+impl Recognizer {
+    /// From a template pack (the json the app saves), the built-in gestures without one.
+    #[new]
+    #[pyo3(signature = (pack=None, backend="dollar1"))]
+    fn new(pack: Option<&str>, backend: &str) -> PyResult<Self> {
+        let backend = self::backend(backend)?;
+        Ok(Self(match pack {
+            Some(json) => engine::Recognizer::from_pack(json, backend).map_err(|error| PyValueError::new_err(error.to_string()))?,
+            None => {
+                let mut recognizer = engine::Recognizer::default();
+                recognizer.set_backend(backend);
+                recognizer
+            }
+        }))
+    }
+
+    /// From a template pack file.
+    #[staticmethod]
+    #[pyo3(signature = (path, backend="dollar1"))]
+    fn load(path: &str, backend: &str) -> PyResult<Self> {
+        let json = fs::read_to_string(path).map_err(|error| PyIOError::new_err(format!("{}: {}", path, error)))?;
+        Self::new(Some(&json), backend)
+    }
+
+    fn save(&self, path: &str) -> PyResult<()> {
+        fs::write(path, self.0.export_pack()).map_err(|error| PyIOError::new_err(format!("{}: {}", path, error)))
+    }
+
+    fn export_pack(&self) -> String {
+        self.0.export_pack()
+    }
+
+    #[getter]
+    fn get_backend(&self) -> &'static str {
+        self.0.backend().name()
+    }
+
+    #[setter]
+    fn set_backend(&mut self, name: &str) -> PyResult<()> {
+        self.0.set_backend(backend(name)?);
+        Ok(())
+    }
+
+    /// Every gesture with how many templates it has.
+    fn gestures(&self) -> BTreeMap<String, usize> {
+        self.0.templates().iter().map(|(name, set)| (name.clone(), set.len())).collect()
+    }
+
+    /// None when there's nothing to compare with (no templates, no points).
+    fn recognize(&self, points: Vec<Vec<f32>>) -> PyResult<Option<Recognition>> {
+        Ok(self.0.recognize(&stroke(points)?).map(|recognition| Recognition {
+            name: recognition.name,
+            score: recognition.score,
+            recognized: recognition.recognized,
+        }))
+    }
+
+    /// Adds the stroke as a template, returns how many templates the gesture has now.
+    fn add_template(&mut self, name: &str, points: Vec<Vec<f32>>) -> PyResult<usize> {
+        self.0
+            .add_template(name, &stroke(points)?)
+            .ok_or_else(|| PyValueError::new_err("the stroke is too short to become a template"))
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "unistroke")]
+fn unistroke_python(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(path_length, module)?)?;
+    module.add_function(wrap_pyfunction!(resample, module)?)?;
+    module.add_function(wrap_pyfunction!(normalize, module)?)?;
+    module.add_function(wrap_pyfunction!(read_strokes, module)?)?;
+    module.add_class::<Recognition>()?;
+    module.add_class::<Recognizer>()?;
+    Ok(())
+}
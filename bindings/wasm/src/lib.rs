@@ -0,0 +1,79 @@
+// the recognizer for javascript, without the bevy app around it:
+//   const recognizer = new Recognizer(packJson);  // or new Recognizer() for the built-in gestures
+//   const result = recognizer.recognize([[x, y], ...]);  // or [{x, y, t}, ...], t in seconds
+//   recognizer.addTemplate("zorro", points);
+//   localStorage.pack = recognizer.exportPack();
+
+use dollar1_unistroke::engine;
+use dollar1_unistroke::formats::parse_strokes;
+use dollar1_unistroke::recognizer::Backend;
+use dollar1_unistroke::stroke::StrokePoint;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct Recognizer(engine::Recognizer);
+
+#[wasm_bindgen(getter_with_clone)]
+pub struct Recognition {
+    pub name: String, // the closest gesture, also when it's too far away to count
+    pub score: f32,
+    pub recognized: bool,
+}
+
+// points as js hands them over, read the same way as stroke files are
+fn stroke(points: &JsValue) -> Result<Vec<StrokePoint>, JsError> {
+    let json: String = js_sys::JSON::stringify(points)
+        .map_err(|_| JsError::new("points can't be turned into json"))?
+        .into();
+    let mut strokes = parse_strokes(&json)?;
+    match strokes.len() {
+        1 => Ok(strokes.remove(0).stroke),
+        _ => Err(JsError::new("pass the points of one stroke")),
+    }
+}
+
+#[wasm_bindgen]
+impl Recognizer {
+    #[wasm_bindgen(constructor)]
+    pub fn new(pack: Option<String>) -> Result<Recognizer, JsError> {
+        Ok(Self(match pack {
+            Some(json) => engine::Recognizer::from_pack(&json, Backend::default())?,
+            None => engine::Recognizer::default(),
+        }))
+    }
+
+    // undefined when there are no templates
+    pub fn recognize(&self, points: JsValue) -> Result<Option<Recognition>, JsError> {
+        Ok(self.0.recognize(&stroke(&points)?).map(|recognition| Recognition {
+            name: recognition.name,
+            score: recognition.score,
+            recognized: recognition.recognized,
+        }))
+    }
+
+    // returns how many templates the gesture has now
+    #[wasm_bindgen(js_name = addTemplate)]
+    pub fn add_template(&mut self, name: &str, points: JsValue) -> Result<usize, JsError> {
+        self.0
+            .add_template(name, &stroke(&points)?)
+            .ok_or_else(|| JsError::new("the stroke is too short to become a template"))
+    }
+
+    #[wasm_bindgen(js_name = exportPack)]
+    pub fn export_pack(&self) -> String {
+        self.0.export_pack()
+    }
+
+    // "dollar1", "rubine" or "dtw"
+    #[wasm_bindgen(setter)]
+    pub fn set_backend(&mut self, name: &str) -> Result<(), JsError> {
+        let backend = Backend::from_name(name).ok_or_else(|| JsError::new("the backend is one of dollar1, rubine or dtw"))?;
+        self.0.set_backend(backend);
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn backend(&self) -> String {
+        self.0.backend().name().to_string()
+    }
+}
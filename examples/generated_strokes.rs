@@ -0,0 +1,166 @@
+// writes tests/data/generated_strokes.json, the strokes the golden and invariance tests recognize:
+//   cargo run --example generated_strokes --no-default-features > tests/data/generated_strokes.json
+// they're generated, not recorded in the app: every gesture is described here (the corners it's drawn through,
+// or the points a curve passes through) rather than taken from the templates, then drawn the way a hand would:
+// at some size and slant, slowing down into corners, with a shaky pen sampled unevenly in time; seeded, so the file always comes out the same
+
+use std::f32::consts::PI;
+
+use glam::{Mat2, Vec2};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Serialize;
+
+const STROKES_PER_GESTURE: usize = 3;
+
+enum Path {
+    Corners(&'static [(f32, f32)]),
+    Curve(&'static [(f32, f32)]), // a catmull-rom spline through the points
+    Circle,
+}
+
+// in screen coordinates (y down), drawn from the first point, as wide and tall as in the figure of the $1 paper
+// (default_templates.png)
+const GESTURES: [(&str, Path); 16] = [
+    ("Triangle", Path::Corners(&[(0.5, 0.0), (0.0, 0.66), (1.0, 0.66), (0.5, 0.0)])),
+    ("X", Path::Corners(&[(0.0, 0.0), (0.9, 1.0), (0.9, 0.0), (0.0, 1.0)])),
+    ("Rectangle", Path::Corners(&[(0.0, 0.0), (0.0, 0.65), (1.0, 0.65), (1.0, 0.0), (0.0, 0.0)])),
+    ("Circle", Path::Circle),
+    ("Check", Path::Corners(&[(0.0, 0.5), (0.35, 1.0), (1.0, 0.0)])),
+    ("Caret", Path::Corners(&[(0.0, 1.0), (0.5, 0.0), (1.0, 1.0)])),
+    ("Zig-Zag", Path::Corners(&[(0.0, 0.3), (0.2, 0.0), (0.4, 0.3), (0.6, 0.0), (0.8, 0.3), (1.0, 0.0)])),
+    ("Arrow", Path::Corners(&[(0.0, 0.3), (1.0, 0.25), (0.8, 0.0), (1.0, 0.25), (0.8, 0.5)])),
+    ("Left Square Bracket", Path::Corners(&[(0.5, 0.0), (0.0, 0.0), (0.0, 1.0), (0.5, 1.0)])),
+    ("Right Square Bracket", Path::Corners(&[(0.0, 0.0), (0.6, 0.0), (0.6, 1.0), (0.0, 1.0)])),
+    ("V", Path::Corners(&[(0.0, 0.0), (0.5, 1.0), (1.0, 0.0)])),
+    ("Delete", Path::Corners(&[(0.0, 0.0), (0.85, 1.0), (0.0, 1.0), (0.85, 0.0)])),
+    (
+        "Left Curly Bracket",
+        Path::Curve(&[(0.5, 0.0), (0.3, 0.03), (0.25, 0.2), (0.25, 0.4), (0.1, 0.5), (0.25, 0.6), (0.25, 0.8), (0.3, 0.97), (0.5, 1.0)]),
+    ),
+    (
+        "Right Curly Bracket",
+        Path::Curve(&[(0.0, 0.0), (0.2, 0.03), (0.25, 0.2), (0.25, 0.4), (0.4, 0.5), (0.25, 0.6), (0.25, 0.8), (0.2, 0.97), (0.0, 1.0)]),
+    ),
+    ("Star", Path::Corners(&[(0.24, 1.0), (0.6, 0.0), (0.96, 1.0), (0.0, 0.38), (1.2, 0.38), (0.24, 1.0)])),
+    (
+        "Pigtail",
+        Path::Curve(&[(0.0, 0.63), (0.4, 0.53), (0.75, 0.25), (0.65, 0.04), (0.45, 0.1), (0.5, 0.35), (1.0, 0.6)]),
+    ),
+];
+
+#[derive(Serialize)]
+struct Point {
+    x: f32,
+    y: f32,
+    t: f32,
+}
+
+#[derive(Serialize)]
+struct Stroke {
+    name: &'static str,
+    points: Vec<Point>,
+}
+
+// the path densely, with how sharp the turn at every point is (0 straight on, 1 a full reversal)
+fn trace(path: &Path) -> Vec<(Vec2, f32)> {
+    let points: Vec<Vec2> = match path {
+        Path::Corners(corners) => corners
+            .windows(2)
+            .flat_map(|pair| {
+                let (from, to) = (Vec2::from(pair[0]), Vec2::from(pair[1]));
+                (0..40).map(move |i| from.lerp(to, i as f32 / 40.0))
+            })
+            .chain(corners.last().map(|&last| Vec2::from(last)))
+            .collect(),
+        Path::Curve(through) => {
+            let through: Vec<Vec2> = through.iter().map(|&point| Vec2::from(point)).collect();
+            let at = |i: isize| through[i.clamp(0, through.len() as isize - 1) as usize];
+            let mut points = Vec::new();
+            for i in 0..through.len() as isize - 1 {
+                let (p0, p1, p2, p3) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+                for step in 0..40 {
+                    let t = step as f32 / 40.0;
+                    let (t2, t3) = (t * t, t * t * t);
+                    points.push(
+                        0.5 * (2.0 * p1
+                            + (p2 - p0) * t
+                            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+                            + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3),
+                    );
+                }
+            }
+            points.push(*through.last().unwrap());
+            points
+        }
+        // starting at the top, going left (counterclockwise on screen) and a little past where it started
+        Path::Circle => (0..=170)
+            .map(|i| {
+                let angle = i as f32 / 160.0 * 2.0 * PI;
+                Vec2::new(0.5 - 0.5 * angle.sin(), 0.5 - 0.5 * angle.cos())
+            })
+            .collect(),
+    };
+
+    (0..points.len())
+        .map(|i| {
+            let (Some(before), Some(after)) = (i.checked_sub(1).map(|j| points[j]), points.get(i + 1)) else {
+                return (points[i], 0.0);
+            };
+            let (incoming, outgoing) = ((points[i] - before).normalize_or_zero(), (*after - points[i]).normalize_or_zero());
+            (points[i], ((1.0 - incoming.dot(outgoing)) / 2.0).clamp(0.0, 1.0))
+        })
+        .collect()
+}
+
+fn draw(path: &Path, rng: &mut StdRng) -> Vec<Point> {
+    let size = rng.gen_range(120.0..320.0);
+    let slant = Mat2::from_angle(rng.gen_range(-10.0f32..10.0).to_radians());
+    let stretch = Vec2::new(rng.gen_range(0.85..1.15), 1.0); // a little wider or narrower than the figure
+    let origin = Vec2::new(rng.gen_range(100.0..500.0), rng.gen_range(100.0..300.0));
+    let speed = rng.gen_range(400.0..1200.0); // px/s on the straights
+    let shake = rng.gen_range(0.5..2.0); // px
+
+    let traced = trace(path);
+    let mut points = Vec::new();
+    let mut t = 0.0;
+    let mut index = 0.0;
+    let mut previous = origin + slant * (traced[0].0 * stretch * size);
+    while (index as usize) < traced.len() {
+        let (position, sharpness) = traced[index as usize];
+        let position = origin + slant * (position * stretch * size);
+        t += position.distance(previous) / (speed * (1.0 - 0.8 * sharpness.sqrt()));
+        previous = position;
+
+        // mice and pens report every 8 - 16 ms, so a fast hand leaves gaps and a slow one bunches up
+        let dt = rng.gen_range(0.008..0.016);
+        t = (t / dt).ceil() * dt;
+        let jitter = Vec2::new(rng.gen_range(-shake..=shake), rng.gen_range(-shake..=shake));
+        points.push(Point {
+            x: ((position.x + jitter.x) * 10.0).round() / 10.0,
+            y: ((position.y + jitter.y) * 10.0).round() / 10.0,
+            t: (t * 1000.0).round() / 1000.0,
+        });
+        index += rng.gen_range(1.0..4.0);
+    }
+    let (last, _) = traced[traced.len() - 1];
+    let last = origin + slant * (last * stretch * size);
+    points.push(Point {
+        x: (last.x * 10.0).round() / 10.0,
+        y: (last.y * 10.0).round() / 10.0,
+        t: ((t + 0.01) * 1000.0).round() / 1000.0,
+    });
+    points
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(50);
+    let strokes: Vec<Stroke> = GESTURES
+        .iter()
+        .flat_map(|(name, path)| (0..STROKES_PER_GESTURE).map(move |_| (name, path)))
+        .map(|(name, path)| Stroke {
+            name,
+            points: draw(path, &mut rng),
+        })
+        .collect();
+    println!("{}", serde_json::to_string(&strokes).expect("strokes are serializable"));
+}
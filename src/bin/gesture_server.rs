@@ -0,0 +1,63 @@
+// serves the recognizer over http on this machine, see server.rs for the endpoints
+//   gesture_server [--pack PACK] [--addr 127.0.0.1:7878] [--backend dollar1|rubine|dtw] [--origin ORIGIN]..
+// the pack is loaded when it exists (the built-in gestures otherwise) and added templates are saved back to it;
+// web pages can only call it from an --origin given (http://localhost:8080, say), none are by default
+
+use std::fs;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use dollar1_unistroke::engine::Recognizer;
+use dollar1_unistroke::recognizer::Backend;
+use dollar1_unistroke::server::RecognitionService;
+use dollar1_unistroke::templates::stroke_templates;
+
+const USAGE: &str =
+    "usage: gesture_server [--pack PACK] [--addr 127.0.0.1:7878] [--backend dollar1|rubine|dtw] [--origin ORIGIN]..";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("gesture_server: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut pack_path: Option<PathBuf> = None;
+    let mut addr = "127.0.0.1:7878".to_string();
+    let mut backend = Backend::default();
+    let mut origins = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--pack" => pack_path = Some(args.next().ok_or("--pack needs a file")?.into()),
+            "--addr" => addr = args.next().ok_or("--addr needs an address")?,
+            "--origin" => origins.push(args.next().ok_or("--origin needs an origin, like http://localhost:8080")?),
+            "--backend" => {
+                backend = args
+                    .next()
+                    .and_then(|name| Backend::from_name(&name))
+                    .ok_or("--backend is one of dollar1, rubine or dtw")?
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let recognizer = match &pack_path {
+        Some(path) if path.exists() => {
+            let json = fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+            Recognizer::from_pack(&json, backend).map_err(|error| format!("{}: {}", path.display(), error))?
+        }
+        _ => Recognizer::new(stroke_templates(), backend),
+    };
+
+    let listener = TcpListener::bind(&addr).map_err(|error| format!("{}: {}", addr, error))?;
+    println!("recognizing gestures on http://{}", addr);
+    RecognitionService::new(recognizer, pack_path).with_origins(origins).serve(&listener);
+    Ok(())
+}
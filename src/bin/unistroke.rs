@@ -0,0 +1,285 @@
+// the recognizer from the command line, strokes come from files (or stdin) in any format formats.rs reads:
+//   unistroke recognize [FILE] [--pack PACK] [--backend dollar1|rubine|dtw]
+//   unistroke add NAME [FILE] --pack PACK [--speed MIN MAX]
+//   unistroke list [--pack PACK]
+//   unistroke evaluate PATH... [--pack PACK]
+//   unistroke replay SESSION
+// without --pack the built-in gestures are used
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::{fs, io};
+
+use dollar1_unistroke::formats::{parse_strokes, StrokeFile};
+use dollar1_unistroke::pack;
+use dollar1_unistroke::recognizer::{best_match_by, normalize, to_template, Backend, REJECT_DISTANCE};
+use dollar1_unistroke::rubine::RubineClassifier;
+use dollar1_unistroke::session::{self, read_session};
+use dollar1_unistroke::stroke::positions;
+use dollar1_unistroke::templates::{stroke_templates, Template};
+use dollar1_unistroke::temporal::{temporal_features, SpeedRange};
+
+const USAGE: &str = "usage:
+  unistroke recognize [FILE] [--pack PACK] [--backend dollar1|rubine|dtw]
+  unistroke add NAME [FILE] --pack PACK [--speed MIN MAX]
+  unistroke list [--pack PACK]
+  unistroke evaluate PATH... [--pack PACK]
+  unistroke replay SESSION
+strokes are read from stdin when no FILE is given, --speed only matches the templates to strokes drawn
+at an average of MIN to MAX px/s";
+
+struct Args {
+    positional: Vec<String>,
+    pack: Option<PathBuf>,
+    backend: Backend,
+    speed: Option<SpeedRange>,
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(command) = args.next() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    let result = parse_args(args).and_then(|args| match command.as_str() {
+        "recognize" => recognize(&args),
+        "add" => add(&args),
+        "list" => list(&args),
+        "evaluate" => evaluate(&args),
+        "replay" => replay(&args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(format!("unknown command {}\n{}", other, USAGE)),
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("unistroke: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        positional: Vec::new(),
+        pack: None,
+        backend: Backend::default(),
+        speed: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--pack" => parsed.pack = Some(args.next().ok_or("--pack needs a file")?.into()),
+            "--backend" => {
+                parsed.backend = args
+                    .next()
+                    .and_then(|name| Backend::from_name(&name))
+                    .ok_or("--backend is one of dollar1, rubine or dtw")?
+            }
+            "--speed" => {
+                let mut bound = || args.next().and_then(|bound| bound.parse::<f32>().ok()).filter(|bound| *bound >= 0.0);
+                let (Some(min), Some(max)) = (bound(), bound()) else {
+                    return Err("--speed needs the slowest and fastest average speed in px/s".to_string());
+                };
+                if min > max {
+                    return Err("--speed takes the slowest speed first".to_string());
+                }
+                parsed.speed = Some(SpeedRange { min, max });
+            }
+            _ => parsed.positional.push(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+fn load_templates(pack: Option<&Path>) -> Result<HashMap<String, HashSet<Template>>, String> {
+    match pack {
+        Some(path) => {
+            let json = fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+            pack::from_json(&json).map_err(|error| format!("{}: {}", path.display(), error))
+        }
+        None => Ok(stroke_templates()),
+    }
+}
+
+// a file, or stdin for a missing file or "-"
+fn read_strokes(file: Option<&Path>) -> Result<Vec<StrokeFile>, String> {
+    let file = file.filter(|path| *path != Path::new("-"));
+    let text = match file {
+        Some(path) => fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?,
+        None => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).map_err(|error| format!("stdin: {}", error))?;
+            text
+        }
+    };
+    let source = file.map_or("stdin".into(), Path::to_string_lossy);
+    parse_strokes(&text).map_err(|error| format!("{}: {}", source, error))
+}
+
+// one line per stroke in the file: the gesture and its distance, or the closest one when it's too far
+fn recognize(args: &Args) -> Result<(), String> {
+    let templates = load_templates(args.pack.as_deref())?;
+    let rubine = match args.backend {
+        Backend::Rubine => Some(RubineClassifier::train_with_synthetic(&templates).ok_or("no templates to train rubine with")?),
+        _ => None,
+    };
+
+    for file in read_strokes(args.positional.first().map(Path::new))? {
+        let raw_points = positions(&file.stroke);
+        let points = normalize(&raw_points);
+        let temporal = temporal_features(&file.stroke);
+        let best = best_match_by(args.backend, &points, &raw_points, temporal.as_ref(), &templates, rubine.as_ref());
+        match best {
+            Some(best) if best.distance <= REJECT_DISTANCE => println!("{}\t{:.2}", best.name, best.distance),
+            Some(best) => println!("not recognized (closest {} at {:.2})", best.name, best.distance),
+            None => println!("not recognized"),
+        }
+    }
+    Ok(())
+}
+
+// the pack is created when it doesn't exist yet, starting empty rather than from the built-in gestures
+fn add(args: &Args) -> Result<(), String> {
+    let name = args.positional.first().ok_or(USAGE)?;
+    let path = args.pack.as_deref().ok_or("add needs --pack to know where the template goes")?;
+    let mut templates = if path.exists() {
+        load_templates(Some(path))?
+    } else {
+        HashMap::new()
+    };
+
+    let files = read_strokes(args.positional.get(1).map(Path::new))?;
+    for file in files.iter() {
+        let mut template = to_template(&normalize(&positions(&file.stroke))).ok_or("a stroke is too short to become a template")?;
+        template.stroke = file.stroke.clone();
+        template.speed = args.speed;
+        templates.entry(name.clone()).or_default().insert(template);
+    }
+
+    fs::write(path, pack::to_json(&templates)).map_err(|error| format!("{}: {}", path.display(), error))?;
+    println!("added {} template(s) of {} to {}", files.len(), name, path.display());
+    Ok(())
+}
+
+fn list(args: &Args) -> Result<(), String> {
+    let templates = load_templates(args.pack.as_deref())?;
+    let sorted: BTreeMap<&String, usize> = templates.iter().map(|(name, set)| (name, set.len())).collect();
+    for (name, count) in sorted {
+        println!("{}\t{}", name, count);
+    }
+    Ok(())
+}
+
+// how many labelled strokes each backend gets right, per gesture and overall
+// (the $1 logs' names are read as the built-in gestures', formats::DOLLAR1_NAMES)
+fn evaluate(args: &Args) -> Result<(), String> {
+    if args.positional.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    let templates = load_templates(args.pack.as_deref())?;
+    let rubine = RubineClassifier::train_with_synthetic(&templates);
+
+    let mut samples = Vec::new();
+    for path in args.positional.iter() {
+        for file in stroke_files(Path::new(path))? {
+            for stroke in read_strokes(Some(&file))? {
+                match stroke.name {
+                    Some(name) => samples.push((name, stroke.stroke)),
+                    None => eprintln!("unistroke: {} has a stroke without a name, skipped", file.display()),
+                }
+            }
+        }
+    }
+    if samples.is_empty() {
+        return Err("no labelled strokes to evaluate".to_string());
+    }
+
+    // name -> (strokes, correct per backend)
+    let mut results: BTreeMap<&str, (usize, [usize; Backend::ALL.len()])> = BTreeMap::new();
+    for (name, stroke) in samples.iter() {
+        let raw_points = positions(stroke);
+        let points = normalize(&raw_points);
+        let temporal = temporal_features(stroke);
+        let entry = results.entry(name).or_default();
+        entry.0 += 1;
+        for (correct, backend) in entry.1.iter_mut().zip(Backend::ALL) {
+            let best = best_match_by(backend, &points, &raw_points, temporal.as_ref(), &templates, rubine.as_ref());
+            if best.is_some_and(|best| best.name == name) {
+                *correct += 1;
+            }
+        }
+    }
+
+    let percent = |correct: usize, total: usize| 100.0 * correct as f32 / total as f32;
+    print!("{:<24}{:>8}", "gesture", "strokes");
+    for backend in Backend::ALL {
+        print!("{:>10}", backend.name());
+    }
+    println!();
+    let mut overall = [0; Backend::ALL.len()];
+    for (name, (total, correct)) in results.iter() {
+        print!("{:<24}{:>8}", name, total);
+        for (sum, correct) in overall.iter_mut().zip(correct) {
+            *sum += correct;
+            print!("{:>9.1}%", percent(*correct, *total));
+        }
+        if !templates.contains_key(*name) {
+            print!("  (not in the pack)");
+        }
+        println!();
+    }
+    print!("{:<24}{:>8}", "overall", samples.len());
+    for correct in overall {
+        print!("{:>9.1}%", percent(correct, samples.len()));
+    }
+    println!();
+    Ok(())
+}
+
+// every stroke of a recorded session recognized again, the ones that come out different are what to look at
+fn replay(args: &Args) -> Result<(), String> {
+    let path = args.positional.first().ok_or(USAGE)?;
+    let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+    let events = read_session(&text).map_err(|error| format!("{}: {}", path, error))?;
+
+    let replayed = session::replay(&events);
+    for stroke in replayed.iter() {
+        if stroke.matches() {
+            println!("{:>9.2}s  {}", stroke.at, stroke.replayed);
+        } else {
+            println!("{:>9.2}s  {}, recorded as {}  DIFFERENT", stroke.at, stroke.replayed, stroke.recorded);
+        }
+    }
+    let different = replayed.iter().filter(|stroke| !stroke.matches()).count();
+    println!("{} strokes, {} came out different", replayed.len(), different);
+    if different > 0 {
+        return Err(format!("{} replayed strokes differ from the recording", different));
+    }
+    Ok(())
+}
+
+// the file itself, or every .json and .xml file somewhere under the directory
+fn stroke_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = Vec::new();
+    let entries = fs::read_dir(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+    for entry in entries {
+        let entry = entry.map_err(|error| format!("{}: {}", path.display(), error))?.path();
+        if entry.is_dir() {
+            files.extend(stroke_files(&entry)?);
+        } else if entry.extension().is_some_and(|extension| extension == "json" || extension == "xml") {
+            files.push(entry);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
@@ -0,0 +1,72 @@
+// painting straight into the board's pixel buffer instead of going through Image::set_color_at per sample
+// every segment of a stroke is stamped as a capsule (a line with round ends), each pixel near it is visited
+// once and blended by how much of it the capsule covers, which gives antialiased edges for free
+
+use bevy::prelude::*;
+
+const BYTES_PER_PIXEL: usize = 4; // the board and thumbnails are all Rgba8UnormSrgb
+
+pub fn clear(image: &mut Image, color: Color) {
+    let color = color.to_srgba().to_u8_array();
+    for pixel in image.data.chunks_exact_mut(BYTES_PER_PIXEL) {
+        pixel.copy_from_slice(&color);
+    }
+}
+
+pub fn stamp_disc(image: &mut Image, center: Vec2, radius: f32, color: Color) {
+    stamp_line(image, center, center, radius, color);
+}
+
+pub fn stamp_line(image: &mut Image, from: Vec2, to: Vec2, radius: f32, color: Color) {
+    let size = image.size();
+    let color = color.to_srgba().to_u8_array();
+
+    // only the pixels in the capsule's bounding box can be touched
+    let min = (from.min(to) - Vec2::splat(radius + 1.0)).max(Vec2::ZERO);
+    let max = (from.max(to) + Vec2::splat(radius + 1.0)).min(size.as_vec2() - Vec2::ONE);
+    if min.x > max.x || min.y > max.y {
+        return;
+    }
+
+    let segment = to - from;
+    let segment_length_squared = segment.length_squared();
+    for y in (min.y as u32)..=(max.y as u32) {
+        for x in (min.x as u32)..=(max.x as u32) {
+            // distance from the middle of the pixel to the closest point of the segment
+            let pixel_center = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let t = if segment_length_squared > 0.0 {
+                ((pixel_center - from).dot(segment) / segment_length_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let distance = pixel_center.distance(from + segment * t);
+
+            let coverage = (radius + 0.5 - distance).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                let index = (y as usize * size.x as usize + x as usize) * BYTES_PER_PIXEL;
+                blend(&mut image.data[index..index + BYTES_PER_PIXEL], &color, coverage);
+            }
+        }
+    }
+}
+
+pub fn stamp_polyline(image: &mut Image, points: &[Vec2], radius: f32, color: Color) {
+    for pair in points.windows(2) {
+        stamp_line(image, pair[0], pair[1], radius, color);
+    }
+}
+
+// the color over the pixel, both with straight (not premultiplied) alpha, so strokes keep their color on a transparent board
+fn blend(pixel: &mut [u8], color: &[u8; BYTES_PER_PIXEL], coverage: f32) {
+    let source_alpha = color[3] as f32 / 255.0 * coverage;
+    let target_alpha = pixel[3] as f32 / 255.0;
+    let alpha = source_alpha + target_alpha * (1.0 - source_alpha);
+    if alpha <= 0.0 {
+        return;
+    }
+    for (channel, source) in pixel.iter_mut().zip(color.iter()).take(3) {
+        let blended = (*source as f32 * source_alpha + *channel as f32 * target_alpha * (1.0 - source_alpha)) / alpha;
+        *channel = blended.round() as u8;
+    }
+    pixel[3] = (alpha * 255.0).round() as u8;
+}
@@ -0,0 +1,213 @@
+// every "Add" puts another template in the set and recognize() compares against all of them,
+// so this shrinks the sets back down:
+// 1. (optional) near-duplicates of the same gesture are clustered and replaced by their medoid
+// 2. templates that never change a leave-one-out decision are dropped
+// the leave-one-out accuracy (and the accuracy on an evaluation set, if one is given) is reported before and after
+
+use std::collections::{HashMap, HashSet};
+
+use glam::Vec2;
+
+use crate::recognizer::{distance_at_best_angle, normalize, recognize};
+use crate::templates::{sorted, Template};
+
+pub struct CondenseOptions {
+    // templates of the same gesture closer than this (same units as recognize()'s score) get merged into one
+    pub merge_distance: Option<f32>,
+}
+
+impl Default for CondenseOptions {
+    fn default() -> Self {
+        Self { merge_distance: Some(0.5) }
+    }
+}
+
+#[derive(Debug)]
+pub struct CondenseReport {
+    pub templates_before: usize,
+    pub templates_after: usize,
+    pub merged: usize,
+    pub removed: usize,
+    pub accuracy_before: f32,
+    pub accuracy_after: f32,
+    pub evaluation_accuracy_before: Option<f32>,
+    pub evaluation_accuracy_after: Option<f32>,
+}
+
+struct Entry<'a> {
+    name: String,
+    template: &'a Template,
+}
+
+// evaluation strokes are raw (not normalized) points, like synthetic::dataset() makes them
+pub fn condense(
+    templates: &HashMap<String, HashSet<Template>>,
+    options: &CondenseOptions,
+    evaluation: &[(String, Vec<Vec2>)],
+) -> (HashMap<String, HashSet<Template>>, CondenseReport) {
+    // in a stable order, ties (in the clustering, between equally near templates) then go the same way every run
+    let mut names: Vec<&String> = templates.keys().collect();
+    names.sort();
+    let entries: Vec<Entry> = names
+        .into_iter()
+        .flat_map(|name| {
+            sorted(&templates[name]).into_iter().map(|template| Entry {
+                name: name.clone(),
+                template,
+            })
+        })
+        .collect();
+
+    // distances[q][r] is how far template q is from template r, computed once since everything below needs it
+    let distances: Vec<Vec<f32>> = entries
+        .iter()
+        .map(|q| {
+            entries
+                .iter()
+                .map(|r| distance_at_best_angle(&q.template.points, &r.template.points))
+                .collect()
+        })
+        .collect();
+
+    let everything: Vec<usize> = (0..entries.len()).collect();
+    let accuracy_before = leave_one_out_accuracy(&entries, &distances, &everything);
+
+    let mut kept = match options.merge_distance {
+        Some(merge_distance) => merge_near_duplicates(&entries, &distances, merge_distance),
+        None => everything.clone(),
+    };
+    let merged = entries.len() - kept.len();
+
+    // the decisions the merged set makes are the ones every removal has to preserve
+    let reference: Vec<Option<&str>> = (0..entries.len())
+        .map(|q| nearest(&distances, &kept, q).map(|r| entries[r].name.as_str()))
+        .collect();
+
+    let mut index = 0;
+    while index < kept.len() {
+        let candidate = kept[index];
+        let others_in_class = kept
+            .iter()
+            .filter(|&&r| r != candidate && entries[r].name == entries[candidate].name)
+            .count();
+        if others_in_class == 0 {
+            // never empty a gesture out completely
+            index += 1;
+            continue;
+        }
+
+        let without: Vec<usize> = kept.iter().copied().filter(|&r| r != candidate).collect();
+        let unchanged = (0..entries.len()).all(|q| {
+            nearest(&distances, &without, q).map(|r| entries[r].name.as_str()) == reference[q]
+        });
+        if unchanged {
+            kept = without;
+        } else {
+            index += 1;
+        }
+    }
+    let removed = entries.len() - merged - kept.len();
+
+    let mut condensed: HashMap<String, HashSet<Template>> = HashMap::new();
+    for &r in kept.iter() {
+        condensed
+            .entry(entries[r].name.clone())
+            .or_default()
+            .insert(entries[r].template.clone());
+    }
+
+    let accuracy_after = leave_one_out_accuracy(&entries, &distances, &kept);
+    let (evaluation_accuracy_before, evaluation_accuracy_after) = if evaluation.is_empty() {
+        (None, None)
+    } else {
+        (
+            Some(accuracy(templates, evaluation)),
+            Some(accuracy(&condensed, evaluation)),
+        )
+    };
+
+    let report = CondenseReport {
+        templates_before: entries.len(),
+        templates_after: kept.len(),
+        merged,
+        removed,
+        accuracy_before,
+        accuracy_after,
+        evaluation_accuracy_before,
+        evaluation_accuracy_after,
+    };
+    (condensed, report)
+}
+
+// fraction of the (raw) strokes that are recognized as the gesture they are labelled with
+pub fn accuracy(templates: &HashMap<String, HashSet<Template>>, samples: &[(String, Vec<Vec2>)]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let correct = samples
+        .iter()
+        .filter(|(name, points)| recognize(&normalize(points), templates).0 == *name)
+        .count();
+    correct as f32 / samples.len() as f32
+}
+
+// nearest template to q out of the candidates, q itself left out
+fn nearest(distances: &[Vec<f32>], candidates: &[usize], q: usize) -> Option<usize> {
+    candidates
+        .iter()
+        .copied()
+        .filter(|&r| r != q)
+        .min_by(|&a, &b| distances[q][a].total_cmp(&distances[q][b]))
+}
+
+// every original template is classified by the kept ones (without itself)
+fn leave_one_out_accuracy(entries: &[Entry], distances: &[Vec<f32>], kept: &[usize]) -> f32 {
+    if entries.is_empty() {
+        return 0.0;
+    }
+    let correct = (0..entries.len())
+        .filter(|&q| nearest(distances, kept, q).is_some_and(|r| entries[r].name == entries[q].name))
+        .count();
+    correct as f32 / entries.len() as f32
+}
+
+// greedy clustering per gesture: the template with the most neighbours within merge_distance takes them
+// all as its cluster, and the cluster is replaced by its medoid (the member closest to all the others)
+fn merge_near_duplicates(entries: &[Entry], distances: &[Vec<f32>], merge_distance: f32) -> Vec<usize> {
+    let close = |a: usize, b: usize| distances[a][b].max(distances[b][a]) <= merge_distance;
+
+    let mut unassigned: Vec<usize> = (0..entries.len()).collect();
+    let mut medoids = Vec::new();
+    while !unassigned.is_empty() {
+        let (seed, _) = unassigned
+            .iter()
+            .map(|&a| {
+                let neighbours = unassigned
+                    .iter()
+                    .filter(|&&b| entries[a].name == entries[b].name && close(a, b))
+                    .count();
+                (a, neighbours)
+            })
+            .max_by_key(|&(_, neighbours)| neighbours)
+            .unwrap();
+
+        let cluster: Vec<usize> = unassigned
+            .iter()
+            .copied()
+            .filter(|&b| entries[seed].name == entries[b].name && close(seed, b))
+            .collect();
+        let medoid = *cluster
+            .iter()
+            .min_by(|&&a, &&b| {
+                let sum_a: f32 = cluster.iter().map(|&c| distances[a][c]).sum();
+                let sum_b: f32 = cluster.iter().map(|&c| distances[b][c]).sum();
+                sum_a.total_cmp(&sum_b)
+            })
+            .unwrap();
+
+        medoids.push(medoid);
+        unassigned.retain(|r| !cluster.contains(r));
+    }
+    medoids.sort();
+    medoids
+}
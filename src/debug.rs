@@ -0,0 +1,136 @@
+// F3 toggles a gizmo overlay of what every step of the pipeline did to the last stroke:
+// raw input points, the resampled points, the indicative angle, the bounding box the scaling used,
+// and (bottom right) the normalized stroke next to the template it matched
+
+use bevy::prelude::*;
+use dollar1_unistroke::plugin::LastStroke;
+use dollar1_unistroke::recognizer::SCALE_SIZE;
+
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+const RAW_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+const RESAMPLED_COLOR: Color = Color::srgb(0.0, 0.8, 1.0);
+const ANGLE_COLOR: Color = Color::srgb(1.0, 1.0, 0.0);
+const BOUNDS_COLOR: Color = Color::srgb(1.0, 0.0, 1.0);
+const NORMALIZED_COLOR: Color = Color::srgb(0.0, 1.0, 0.0);
+const TEMPLATE_COLOR: Color = Color::srgb(1.0, 0.3, 0.0);
+const PREVIEW_SIZE: f32 = SCALE_SIZE * 1.6;
+
+#[derive(Resource)]
+pub struct DebugOverlay(pub bool);
+
+#[derive(Component)]
+pub struct DebugText;
+
+pub fn spawn_debug_text(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(ANGLE_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(5.0),
+            top: Val::Px(30.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        DebugText,
+    ));
+}
+
+pub fn toggle_debug_overlay(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut debug_overlay: ResMut<DebugOverlay>,
+    mut visibility: Single<&mut Visibility, With<DebugText>>,
+) {
+    if keys.just_pressed(TOGGLE_KEY) {
+        debug_overlay.0 = !debug_overlay.0;
+        **visibility = if debug_overlay.0 { Visibility::Visible } else { Visibility::Hidden };
+    }
+}
+
+pub fn draw_debug_overlay(
+    debug_overlay: Res<DebugOverlay>,
+    last_stroke: Res<LastStroke>,
+    window: Single<&Window>,
+    mut gizmos: Gizmos,
+    mut text: Single<&mut Text, With<DebugText>>,
+) {
+    if !debug_overlay.0 {
+        return;
+    }
+    let Some(normalization) = last_stroke.normalization else {
+        text.0 = format!("no stroke yet ({:?} to hide)", TOGGLE_KEY);
+        return;
+    };
+
+    // board points are cursor positions, gizmos live in world space with the camera in the middle of the window
+    let half_size = window.size() / 2.0;
+    let to_world = |point: Vec2| Vec2::new(point.x - half_size.x, half_size.y - point.y);
+
+    gizmos.linestrip_2d(last_stroke.stroke.iter().map(|point| to_world(point.pos)), RAW_COLOR);
+    for point in last_stroke.stroke.iter() {
+        gizmos.circle_2d(to_world(point.pos), 1.5, RAW_COLOR);
+    }
+
+    let resampled = normalization.denormalize(&last_stroke.normalized, 0.0);
+    for point in resampled.iter() {
+        gizmos.circle_2d(to_world(*point), 3.0, RESAMPLED_COLOR);
+    }
+
+    // the indicative angle is the angle from the centroid to the first point
+    if let Some(first) = resampled.first() {
+        gizmos.circle_2d(to_world(normalization.centroid), 4.0, ANGLE_COLOR);
+        gizmos.arrow_2d(to_world(normalization.centroid), to_world(*first), ANGLE_COLOR);
+    }
+
+    // the box scale_and_translate measured was in the rotated space, so it comes out rotated here
+    let (mut min, mut max) = (Vec2::MAX, Vec2::MIN);
+    for point in last_stroke.normalized.iter() {
+        min = min.min(*point);
+        max = max.max(*point);
+    }
+    let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y), min];
+    gizmos.linestrip_2d(
+        normalization.denormalize(&corners, 0.0).into_iter().map(to_world),
+        BOUNDS_COLOR,
+    );
+
+    // side by side previews in the bottom right corner
+    let template_center = Vec2::new(half_size.x - PREVIEW_SIZE / 2.0 - 10.0, -half_size.y + PREVIEW_SIZE / 2.0 + 10.0);
+    let normalized_center = template_center - Vec2::new(PREVIEW_SIZE + 10.0, 0.0);
+    // the normalized space has y going down like the cursor, so it is flipped like to_world does
+    let flip = |point: &Vec2| Vec2::new(point.x, -point.y);
+
+    gizmos.rect_2d(normalized_center, Vec2::splat(PREVIEW_SIZE), RAW_COLOR);
+    gizmos.linestrip_2d(last_stroke.normalized.iter().map(|point| flip(point) + normalized_center), NORMALIZED_COLOR);
+    if let Some(first) = last_stroke.normalized.first() {
+        gizmos.circle_2d(flip(first) + normalized_center, 3.0, NORMALIZED_COLOR);
+    }
+
+    gizmos.rect_2d(template_center, Vec2::splat(PREVIEW_SIZE), RAW_COLOR);
+    if let Some((_, template, _)) = &last_stroke.template {
+        gizmos.linestrip_2d(template.iter().map(|point| flip(point) + template_center), TEMPLATE_COLOR);
+        if let Some(first) = template.first() {
+            gizmos.circle_2d(flip(first) + template_center, 3.0, TEMPLATE_COLOR);
+        }
+    }
+
+    let (name, best_angle) = match &last_stroke.template {
+        Some((name, _, best_angle)) => (name.as_str(), *best_angle),
+        None => ("nothing", 0.0),
+    };
+    text.0 = format!(
+        "raw points: {}\nresampled points: {}\nindicative angle: {:.1} deg\nscale: {:.3} x {:.3}\nmatched {} at {:.1} deg\n({:?} to hide)",
+        last_stroke.stroke.len(),
+        last_stroke.normalized.len(),
+        normalization.indicative_angle.to_degrees(),
+        normalization.scale.x,
+        normalization.scale.y,
+        name,
+        best_angle.to_degrees(),
+        TOGGLE_KEY,
+    );
+}
@@ -0,0 +1,102 @@
+// the recognizer without an app around it: templates, the backend that picks between them and what it was trained on
+// what the server and the bindings share, the plugin keeps the same pieces in its own resources
+
+use std::collections::{HashMap, HashSet};
+
+use crate::pack::{self, PackError};
+use crate::recognizer::{best_match_by, normalize, to_template, Backend, REJECT_DISTANCE};
+use crate::rubine::RubineClassifier;
+use crate::stroke::{positions, StrokePoint};
+use crate::templates::{stroke_templates, Template};
+use crate::temporal::temporal_features;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recognition {
+    pub name: String, // the closest gesture, also when it's too far away to count
+    pub score: f32,
+    pub recognized: bool,
+}
+
+pub struct Recognizer {
+    templates: HashMap<String, HashSet<Template>>,
+    backend: Backend,
+    rubine: Option<RubineClassifier>, // only trained when it's the backend
+}
+
+impl Default for Recognizer {
+    // the built-in gestures
+    fn default() -> Self {
+        Self::new(stroke_templates(), Backend::default())
+    }
+}
+
+impl Recognizer {
+    pub fn new(templates: HashMap<String, HashSet<Template>>, backend: Backend) -> Self {
+        let mut recognizer = Self {
+            templates,
+            backend,
+            rubine: None,
+        };
+        recognizer.train();
+        recognizer
+    }
+
+    pub fn from_pack(json: &str, backend: Backend) -> Result<Self, PackError> {
+        Ok(Self::new(pack::from_json(json)?, backend))
+    }
+
+    fn train(&mut self) {
+        self.rubine = match self.backend {
+            Backend::Rubine => RubineClassifier::train_with_synthetic(&self.templates),
+            _ => None,
+        };
+    }
+
+    pub fn templates(&self) -> &HashMap<String, HashSet<Template>> {
+        &self.templates
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend;
+        self.train();
+    }
+
+    pub fn export_pack(&self) -> String {
+        pack::to_json(&self.templates)
+    }
+
+    // a raw stroke, as it was captured; None when it has no shape to recognize: empty, or a click that never moved
+    pub fn recognize(&self, stroke: &[StrokePoint]) -> Option<Recognition> {
+        if stroke.is_empty() {
+            return None;
+        }
+        let raw_points = positions(stroke);
+        let points = normalize(&raw_points);
+        let temporal = temporal_features(stroke);
+        let best = best_match_by(self.backend, &points, &raw_points, temporal.as_ref(), &self.templates, self.rubine.as_ref())?;
+        Some(Recognition {
+            name: best.name.to_string(),
+            score: best.distance,
+            recognized: best.distance <= REJECT_DISTANCE,
+        })
+    }
+
+    // how many templates the gesture has now, None when the stroke is too short to become one
+    pub fn add_template(&mut self, name: &str, stroke: &[StrokePoint]) -> Option<usize> {
+        if stroke.is_empty() {
+            return None;
+        }
+        let mut template = to_template(&normalize(&positions(stroke)))?;
+        template.stroke = stroke.to_vec();
+
+        let set = self.templates.entry(name.to_string()).or_default();
+        set.insert(template);
+        let count = set.len();
+        self.train();
+        Some(count)
+    }
+}
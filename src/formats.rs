@@ -0,0 +1,178 @@
+// strokes as other tools write them: a json array of points ([x, y] pairs or {"x", "y", "t"} objects, optionally
+// wrapped as {"name", "points"} or a list of those), or the xml gesture logs of the $1 paper
+// (<Gesture Name="arrow01"><Point X="" Y="" T=""/>...</Gesture>, T in milliseconds), whose names are
+// turned into the built-in gestures' (see DOLLAR1_NAMES) so the logs can be evaluated against them
+
+use std::fmt;
+
+use glam::Vec2;
+use serde::Deserialize;
+
+use crate::stroke::StrokePoint;
+
+// the gestures of the $1 logs (their names without the sample number) and the built-in gesture each one is,
+// the logs' question_mark has no built-in gesture and keeps its own name
+pub const DOLLAR1_NAMES: [(&str, &str); 16] = [
+    ("triangle", "Triangle"),
+    ("x", "X"),
+    ("rectangle", "Rectangle"),
+    ("circle", "Circle"),
+    ("check", "Check"),
+    ("caret", "Caret"),
+    ("zig_zag", "Zig-Zag"),
+    ("arrow", "Arrow"),
+    ("left_sq_bracket", "Left Square Bracket"),
+    ("right_sq_bracket", "Right Square Bracket"),
+    ("v", "V"),
+    ("delete_mark", "Delete"),
+    ("left_curly_brace", "Left Curly Bracket"),
+    ("right_curly_brace", "Right Curly Bracket"),
+    ("star", "Star"),
+    ("pigtail", "Pigtail"),
+];
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PointFile {
+    Pair([f32; 2]),
+    Full {
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        t: f32, // seconds
+    },
+}
+
+#[derive(Deserialize)]
+struct NamedFile {
+    #[serde(default)]
+    name: Option<String>,
+    points: Vec<PointFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StrokesFile {
+    Points(Vec<PointFile>),
+    One(NamedFile),
+    Many(Vec<NamedFile>),
+}
+
+// a stroke read from a file, with the gesture it's labelled as if the file says
+#[derive(Clone, Debug)]
+pub struct StrokeFile {
+    pub name: Option<String>,
+    pub stroke: Vec<StrokePoint>,
+}
+
+#[derive(Debug)]
+pub enum FormatError {
+    Json(serde_json::Error),
+    Xml(String),
+    Empty,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Json(error) => write!(f, "invalid stroke json: {}", error),
+            FormatError::Xml(error) => write!(f, "invalid stroke xml: {}", error),
+            FormatError::Empty => write!(f, "no stroke points found"),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl From<serde_json::Error> for FormatError {
+    fn from(error: serde_json::Error) -> Self {
+        FormatError::Json(error)
+    }
+}
+
+// every stroke in the text, the format is told apart by its first character
+pub fn parse_strokes(text: &str) -> Result<Vec<StrokeFile>, FormatError> {
+    let strokes = if text.trim_start().starts_with('<') {
+        parse_xml(text)?
+    } else {
+        parse_json(text)?
+    };
+    if strokes.is_empty() || strokes.iter().any(|stroke| stroke.stroke.is_empty()) {
+        return Err(FormatError::Empty);
+    }
+    Ok(strokes)
+}
+
+fn parse_json(text: &str) -> Result<Vec<StrokeFile>, FormatError> {
+    let to_stroke = |points: Vec<PointFile>| -> Vec<StrokePoint> {
+        points
+            .into_iter()
+            .map(|point| match point {
+                PointFile::Pair([x, y]) => StrokePoint::new(Vec2::new(x, y), 0.0),
+                PointFile::Full { x, y, t } => StrokePoint::new(Vec2::new(x, y), t),
+            })
+            .collect()
+    };
+
+    Ok(match serde_json::from_str(text)? {
+        StrokesFile::Points(points) => vec![StrokeFile {
+            name: None,
+            stroke: to_stroke(points),
+        }],
+        StrokesFile::One(file) => vec![StrokeFile {
+            name: file.name,
+            stroke: to_stroke(file.points),
+        }],
+        StrokesFile::Many(files) => files
+            .into_iter()
+            .map(|file| StrokeFile {
+                name: file.name,
+                stroke: to_stroke(file.points),
+            })
+            .collect(),
+    })
+}
+
+// only as much xml as the gesture logs use: Gesture elements holding Point elements, attributes in double quotes
+fn parse_xml(text: &str) -> Result<Vec<StrokeFile>, FormatError> {
+    let mut strokes = Vec::new();
+    for gesture in text.split("<Gesture").skip(1) {
+        let tag_end = gesture.find('>').ok_or_else(|| FormatError::Xml("unclosed Gesture tag".to_string()))?;
+        // the name is the gesture and the number of the sample, "arrow01" is an Arrow
+        let name = attribute(&gesture[..tag_end], "Name").map(|name| {
+            let name = name.trim_end_matches(|c: char| c.is_ascii_digit());
+            DOLLAR1_NAMES
+                .iter()
+                .find(|(log_name, _)| log_name.eq_ignore_ascii_case(name))
+                .map_or(name, |(_, builtin)| builtin)
+                .to_string()
+        });
+
+        let mut stroke = Vec::new();
+        let mut start_time = None;
+        for point in gesture.split("<Point").skip(1) {
+            let tag = &point[..point.find('>').unwrap_or(point.len())];
+            let number = |name: &str| -> Result<f32, FormatError> {
+                attribute(tag, name)
+                    .ok_or_else(|| FormatError::Xml(format!("a Point has no {} attribute", name)))?
+                    .parse()
+                    .map_err(|_| FormatError::Xml(format!("a Point's {} is not a number", name)))
+            };
+            let (x, y) = (number("X")?, number("Y")?);
+            // milliseconds since some epoch (too big for an f32), strokes keep seconds since they began
+            let t = attribute(tag, "T").and_then(|t| t.parse::<f64>().ok()).map_or(0.0, |t| {
+                let start = *start_time.get_or_insert(t);
+                ((t - start) / 1000.0) as f32
+            });
+            stroke.push(StrokePoint::new(Vec2::new(x, y), t));
+        }
+        strokes.push(StrokeFile { name, stroke });
+    }
+    Ok(strokes)
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let end = tag[start..].find('"')?;
+    Some(&tag[start..start + end])
+}
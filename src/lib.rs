@@ -0,0 +1,17 @@
+#[cfg(feature = "bevy")]
+pub mod brush;
+pub mod condense;
+pub mod engine;
+pub mod formats;
+pub mod pack;
+#[cfg(feature = "bevy")]
+pub mod plugin;
+pub mod recognizer;
+pub mod rubine;
+pub mod server;
+pub mod session;
+pub mod stroke;
+pub mod streaming;
+pub mod synthetic;
+pub mod templates;
+pub mod temporal;
@@ -0,0 +1,338 @@
+// the gesture library panel: every gesture in StrokeTemplates with a thumbnail per template,
+// and buttons to rename a gesture, delete a single template or delete the whole gesture
+
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::render::{
+    render_asset::RenderAssetUsages,
+    render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_simple_text_input::{TextInput, TextInputSubmitEvent, TextInputTextFont};
+use dollar1_unistroke::brush;
+use dollar1_unistroke::plugin::{GestureCapture, StrokeTemplates, BRUSH_COLOR};
+use dollar1_unistroke::templates::Template;
+
+use crate::ResultText;
+
+const THUMBNAIL_SIZE: u32 = 56;
+const THUMBNAIL_PADDING: f32 = 6.0;
+const THUMBNAIL_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const THUMBNAIL_START_COLOR: Color = Color::srgb(0.0, 1.0, 0.0);
+const PANEL_COLOR: Color = Color::srgba(0.1, 0.1, 0.1, 0.95);
+
+#[derive(Resource)]
+pub struct LibraryOpen(pub bool);
+
+#[derive(Component)]
+pub struct LibraryButton;
+
+#[derive(Component)]
+pub struct LibraryPanel;
+
+#[derive(Component)]
+pub struct RenameButton(pub String);
+
+#[derive(Component)]
+pub struct DeleteClassButton(pub String);
+
+#[derive(Component)]
+pub struct DeleteTemplateButton(pub String, pub Template);
+
+// the text box for a rename, remembers which gesture it is renaming
+#[derive(Component)]
+pub struct RenameInput(pub String);
+
+pub fn toggle_library(
+    mut library_open: ResMut<LibraryOpen>,
+    mut capture: ResMut<GestureCapture>,
+    mut interaction_query: Query<(&Interaction, &mut BorderColor), (Changed<Interaction>, With<LibraryButton>)>,
+) {
+    for (interaction, mut border_color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                capture.skip_frame = true;
+                library_open.0 = !library_open.0;
+                border_color.0 = bevy::color::palettes::css::LIGHT_GREEN.into();
+            }
+            _ => {
+                border_color.0 = Color::WHITE;
+            }
+        }
+    }
+}
+
+// the panel is rebuilt from scratch whenever it's opened or the templates change, there aren't enough gestures for that to matter
+pub fn refresh_library(
+    mut commands: Commands,
+    library_open: Res<LibraryOpen>,
+    custom_templates: Res<StrokeTemplates>,
+    panel: Query<Entity, With<LibraryPanel>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !library_open.is_changed() && !custom_templates.is_changed() {
+        return;
+    }
+    for entity in &panel {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !library_open.0 {
+        return;
+    }
+
+    let mut names: Vec<&String> = custom_templates.0.keys().collect();
+    names.sort();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(0.0),
+                top: Val::Px(70.0),
+                width: Val::Px(360.0),
+                max_height: Val::Percent(80.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(12.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                overflow: Overflow::scroll_y(),
+                ..default()
+            },
+            BackgroundColor(PANEL_COLOR),
+            // lets the panel swallow clicks so they don't draw on the board behind it
+            Interaction::default(),
+            ScrollPosition::default(),
+            LibraryPanel,
+        ))
+        .with_children(|panel| {
+            for name in names {
+                let set = &custom_templates.0[name];
+
+                panel
+                    .spawn(Node {
+                        column_gap: Val::Px(8.0),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            Text::new(format!("{} ({})", name, set.len())),
+                            TextFont {
+                                font_size: 18.0,
+                                ..default()
+                            },
+                            TextColor(Color::linear_rgb(0.0, 255.0, 0.0)),
+                            Node {
+                                flex_grow: 1.0,
+                                ..default()
+                            },
+                        ));
+                        row.spawn(small_button(RenameButton(name.clone())))
+                            .with_child(small_button_text("Rename"));
+                        row.spawn(small_button(DeleteClassButton(name.clone())))
+                            .with_child(small_button_text("Delete"));
+                    });
+
+                panel
+                    .spawn(Node {
+                        flex_wrap: FlexWrap::Wrap,
+                        column_gap: Val::Px(6.0),
+                        row_gap: Val::Px(6.0),
+                        ..default()
+                    })
+                    .with_children(|thumbnails| {
+                        for template in set.iter() {
+                            let thumbnail = images.add(render_thumbnail(template));
+                            thumbnails
+                                .spawn(Node {
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                })
+                                .with_children(|cell| {
+                                    cell.spawn((
+                                        ImageNode::new(thumbnail),
+                                        Node {
+                                            width: Val::Px(THUMBNAIL_SIZE as f32),
+                                            height: Val::Px(THUMBNAIL_SIZE as f32),
+                                            border: UiRect::all(Val::Px(1.0)),
+                                            ..default()
+                                        },
+                                        BorderColor(Color::srgb(0.4, 0.4, 0.4)),
+                                    ));
+                                    cell.spawn(small_button(DeleteTemplateButton(name.clone(), template.clone())))
+                                        .with_child(small_button_text("x"));
+                                });
+                        }
+                    });
+            }
+        });
+}
+
+pub fn scroll_library(
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    mut panel: Query<(&Interaction, &mut ScrollPosition), With<LibraryPanel>>,
+) {
+    for event in mouse_wheel_events.read() {
+        let delta = match event.unit {
+            MouseScrollUnit::Line => event.y * 20.0,
+            MouseScrollUnit::Pixel => event.y,
+        };
+        for (interaction, mut scroll_position) in &mut panel {
+            if *interaction != Interaction::None {
+                scroll_position.offset_y = (scroll_position.offset_y - delta).max(0.0);
+            }
+        }
+    }
+}
+
+pub fn handle_library_buttons(
+    mut commands: Commands,
+    mut capture: ResMut<GestureCapture>,
+    mut custom_templates: ResMut<StrokeTemplates>,
+    panel: Query<&Interaction, (Changed<Interaction>, With<LibraryPanel>)>,
+    rename_buttons: Query<(&Interaction, &RenameButton), Changed<Interaction>>,
+    delete_class_buttons: Query<(&Interaction, &DeleteClassButton), Changed<Interaction>>,
+    delete_template_buttons: Query<(&Interaction, &DeleteTemplateButton), Changed<Interaction>>,
+    mut result_text: Single<&mut Text, With<ResultText>>,
+) {
+    if panel.iter().any(|interaction| *interaction == Interaction::Pressed) {
+        capture.skip_frame = true;
+    }
+
+    for (interaction, RenameButton(name)) in &rename_buttons {
+        if *interaction == Interaction::Pressed && !capture.paused {
+            capture.skip_frame = true;
+            capture.paused = true;
+            result_text.0 = format!("Renaming {}", name);
+            commands.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(40.0),
+                    top: Val::Percent(20.0),
+                    width: Val::Px(200.0),
+                    border: UiRect::all(Val::Px(5.0)),
+                    padding: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+                BorderColor(BRUSH_COLOR),
+                BackgroundColor(PANEL_COLOR),
+                TextInput,
+                TextInputTextFont(TextFont {
+                    font_size: 34.,
+                    ..default()
+                }),
+                RenameInput(name.clone()),
+            ));
+        }
+    }
+
+    for (interaction, DeleteClassButton(name)) in &delete_class_buttons {
+        if *interaction == Interaction::Pressed {
+            capture.skip_frame = true;
+            custom_templates.0.remove(name);
+            result_text.0 = format!("{} gesture deleted", name);
+        }
+    }
+
+    for (interaction, DeleteTemplateButton(name, template)) in &delete_template_buttons {
+        if *interaction == Interaction::Pressed {
+            capture.skip_frame = true;
+            if let Some(set) = custom_templates.0.get_mut(name) {
+                set.remove(template);
+                // a gesture without templates can never be recognized, so it goes too
+                if set.is_empty() {
+                    custom_templates.0.remove(name);
+                }
+            }
+            result_text.0 = format!("{} template deleted", name);
+        }
+    }
+}
+
+pub fn rename_input_listener(
+    mut events: EventReader<TextInputSubmitEvent>,
+    mut capture: ResMut<GestureCapture>,
+    mut commands: Commands,
+    rename_inputs: Query<&RenameInput>,
+    mut custom_templates: ResMut<StrokeTemplates>,
+    mut result_text: Single<&mut Text, With<ResultText>>,
+) {
+    for event in events.read() {
+        let Ok(RenameInput(old_name)) = rename_inputs.get(event.entity) else {
+            continue;
+        };
+        let new_name = event.value.trim();
+
+        if !new_name.is_empty() && new_name != old_name {
+            if let Some(set) = custom_templates.0.remove(old_name) {
+                // renaming onto an existing gesture merges the two
+                custom_templates.0.entry(new_name.to_string()).or_default().extend(set);
+            }
+            result_text.0 = format!("{} renamed to {}", old_name, new_name);
+        } else {
+            result_text.0 = format!("{} not renamed", old_name);
+        }
+
+        capture.paused = false;
+        commands.entity(event.entity).despawn_recursive();
+    }
+}
+
+// draws the template's points into a small image, the first point is marked so you can tell which way it was drawn
+fn render_thumbnail(template: &Template) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: THUMBNAIL_SIZE,
+            height: THUMBNAIL_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &(PANEL_COLOR.to_srgba().to_u8_array()),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+
+    let (mut min, mut max) = (Vec2::MAX, Vec2::MIN);
+    for point in template.points.iter() {
+        min = min.min(*point);
+        max = max.max(*point);
+    }
+    let available = THUMBNAIL_SIZE as f32 - 2.0 * THUMBNAIL_PADDING;
+    let scale = available / (max - min).max_element().max(f32::EPSILON);
+    // keep the aspect ratio and center the shape in the image
+    let offset = Vec2::splat(THUMBNAIL_PADDING) + (Vec2::splat(available) - (max - min) * scale) / 2.0;
+    let to_pixel = |point: Vec2| (point - min) * scale + offset;
+
+    let points: Vec<Vec2> = template.points.iter().map(|point| to_pixel(*point)).collect();
+    brush::stamp_polyline(&mut image, &points, 0.6, THUMBNAIL_COLOR);
+    brush::stamp_disc(&mut image, points[0], 1.5, THUMBNAIL_START_COLOR);
+
+    image
+}
+
+fn small_button(marker: impl Component) -> impl Bundle {
+    (
+        Button,
+        Node {
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(3.0)),
+            border: UiRect::all(Val::Px(2.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BorderColor(Color::WHITE),
+        BorderRadius::MAX,
+        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+        marker,
+    )
+}
+
+fn small_button_text(label: &str) -> impl Bundle {
+    (
+        Text::new(label),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.9)),
+    )
+}
@@ -1,238 +1,89 @@
-mod templates;
+// bevy systems take lots of params and long queries, clippy does not like either
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+mod debug;
+mod library;
+
 use std::collections::{HashMap, HashSet};
-use std::f32::consts::PI;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use bevy::dev_tools::fps_overlay::{FpsOverlayConfig, FpsOverlayPlugin};
 use bevy::prelude::*;
-use bevy::render::{
-    render_asset::RenderAssetUsages,
-    render_resource::{Extent3d, TextureDimension, TextureFormat},
-};
 use bevy_simple_text_input::{TextInput, TextInputPlugin, TextInputSubmitEvent, TextInputTextFont};
-use chrono::Utc;
-use templates::Template;
-
-const BRUSH_THICKNESS: u32 = 3;
-const BRUSH_COLOR: Color = Color::linear_rgb(255.0, 255.0, 255.0);
-const BOARD_COLOR: Color = Color::linear_rgb(0.0, 0.0, 0.0);
-const RESAMPLE_TARGET_POINTS: usize = 64;
-const SCALE_SIZE: f32 = 100.0;
-
-#[derive(Resource)]
-struct DrawingBoard(Handle<Image>);
+use dollar1_unistroke::condense::{condense, CondenseOptions};
+use dollar1_unistroke::pack;
+use dollar1_unistroke::plugin::{
+    BoardStrokes, GestureCapture, GestureConfig, GestureRecognized, GestureRecognizerPlugin, GestureRejected,
+    GestureSystems, LastStroke, ProvisionalGuess, Replay, StrokeTemplates, BRUSH_COLOR,
+};
+use dollar1_unistroke::recognizer::{class_distances, to_template, Backend, RESAMPLE_TARGET_POINTS};
+use dollar1_unistroke::rubine::RubineClassifier;
+use dollar1_unistroke::synthetic::{self, SyntheticConfig};
+use dollar1_unistroke::templates::Template;
+use debug::{draw_debug_overlay, spawn_debug_text, toggle_debug_overlay, DebugOverlay};
+use library::{handle_library_buttons, refresh_library, rename_input_listener, scroll_library, toggle_library, LibraryButton, LibraryOpen};
+use rand::{rngs::StdRng, SeedableRng};
 
-#[derive(Resource)]
-struct IsTyping(bool);
+// a brand new gesture has nothing of its own to compare against, so it only conflicts when it's this close to another one
+const CONFLICT_DISTANCE: f32 = 2.0;
 
-#[derive(Resource)]
-struct OverAButton(bool);
+#[derive(Component)]
+struct ResultText;
 
-#[derive(Resource)]
-struct ResampledPoints(Vec<Vec2>);
-#[derive(Resource)]
-struct StrokeTemplates(HashMap<String, HashSet<Template>>);
+#[derive(Component)]
+struct AddGestureButton;
 
 #[derive(Component)]
-struct ResultText;
+struct CondenseButton;
 
-#[derive(PartialEq)]
-enum DrawMoment {
-    Idle,
-    InputEnded,
-    InputBegan(Vec2),
-    Held(Vec2),
-}
+// the text box the name of a new gesture is typed into
+#[derive(Component)]
+struct GestureNameInput;
 
+// a gesture that looked more like another one when it was added, waiting for the user to confirm it
 #[derive(Resource)]
-struct DrawState(DrawMoment);
+struct PendingGesture(Option<(String, Template)>);
 
+// where the templates are saved whenever they change, nowhere without --pack
 #[derive(Resource)]
-struct BrushEnabled(bool); // DISABLE FOR BETTER PERFORMANCE SINCE THEN IT DOES NOT HAVE TO DO 360*BRUSH_THICKNESS ITERATIONS
+struct PackFile(Option<PathBuf>);
 
 #[derive(Component)]
-struct ToggleBrushButton;
+struct ConflictDialog;
 
 #[derive(Component)]
-struct AddGestureButton;
-
-fn resample(total_length: f32, candidate_points: &Vec<Vec2>) -> Vec<Vec2> {
-    let mut resampled_points = Vec::with_capacity(RESAMPLE_TARGET_POINTS);
-    resampled_points.push(candidate_points[0]);
-
-    if candidate_points.len() > 1 {
-        /*
-         distance squared would be faster but using it leads to inaccuracies with the lerping and alpha;
-         sqrting the alpha gives lesser points for some reason;
-        */
-
-        let increment = total_length / (RESAMPLE_TARGET_POINTS) as f32;
-        let mut accumulated_distance = 0.0;
-        let mut previous_point = candidate_points[0];
-
-        for i in 1..candidate_points.len() {
-            let current_point = candidate_points[i];
-            let mut segment_distance = previous_point.distance(current_point);
-
-            while accumulated_distance + segment_distance >= increment
-                && resampled_points.len() < RESAMPLE_TARGET_POINTS
-            {
-                let alpha = (increment - accumulated_distance) / segment_distance;
-                let new_point = previous_point.lerp(current_point, alpha);
-
-                resampled_points.push(new_point);
-
-                previous_point = new_point;
-                accumulated_distance = 0.0;
-                segment_distance = previous_point.distance(current_point);
-            }
-
-            accumulated_distance += segment_distance;
-            previous_point = current_point;
-        }
-    }
-
-    resampled_points
-}
-
-fn get_centroid(points: &Vec<Vec2>) -> Vec2 {
-    let mut sum_x = 0.0;
-    let mut sum_y = 0.0;
-    for point in points.iter() {
-        sum_x += point.x;
-        sum_y += point.y;
-    }
-    sum_x /= points.len() as f32;
-    sum_y /= points.len() as f32;
-    Vec2::new(sum_x, sum_y)
-}
-
-fn rotate_about_centroid(points: &mut Vec<Vec2>) {
-    let centroid = get_centroid(&points);
-    let indicative_angle = ops::atan2(centroid.y - points[0].y, centroid.x - points[0].x) + PI;
-    // rotation of a point about origin formula was x = x'cosx + y'sinx and for y you add pi/2
-    let cos = ops::cos(indicative_angle);
-    let sin = ops::sin(indicative_angle);
-    for point in points.iter_mut() {
-        let x_ = point.x - centroid.x;
-        let y_ = point.y - centroid.y;
-        point.x = x_ * cos + y_ * sin + centroid.x;
-        point.y = y_ * cos - x_ * sin + centroid.y;
-    }
-}
-
-fn scale_and_translate(points: &mut Vec<Vec2>) {
-    // GET BOUNDING BOX CO-ORDS
-    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
-    for point in points.iter() {
-        min_x = min_x.min(point.x);
-        min_y = min_y.min(point.y);
-        max_x = max_x.max(point.x);
-        max_y = max_y.max(point.y);
-    }
-    let b_width = max_x - min_x;
-    let b_height = max_y - min_y;
-
-    // SCALING (SCALING MESSES UP STRAIGHT LINES)
-    for point in points.iter_mut() {
-        point.x = point.x * (SCALE_SIZE / b_width);
-        point.y = point.y * (SCALE_SIZE / b_height);
-    }
-
-    // TRANSLATE TO ORIGIN (offset is for debugging purposes)
-    let centroid = get_centroid(points);
-    for point in points.iter_mut() {
-        point.x += -centroid.x;
-        point.y += -centroid.y;
-    }
-}
-
-fn recognize(points: &Vec<Vec2>, templates: Res<StrokeTemplates>) -> (String, f32) {
-    let mut nearest_distance_squared = f32::MAX;
-    let mut nearest_name = "not recognized";
-    
-    for unistroke in templates.0.iter() {
-        for template in unistroke.1.iter() {
-            let distance = distance_at_best_angle(points, &template.0);
-            if distance < nearest_distance_squared {
-                nearest_distance_squared = distance;
-                nearest_name = unistroke.0;
-            }
-        }
-    }
-
-    (nearest_name.to_string(), nearest_distance_squared)
+enum ConflictChoice {
+    AddAnyway,
+    Cancel,
 }
 
-fn distance_at_best_angle(points: &Vec<Vec2>, template_points: &[Vec2; RESAMPLE_TARGET_POINTS]) -> f32 {
-    // follows the golden-section search algorithm
-    const DELTA_THETA: f32 = 0.03490658503; // 2 deg in rads
-    const INVERSE_PHI: f32 = 0.61803398875;
-
-    let mut theta_max = 0.78539816339; // 45 deg in rads
-    let mut theta_min = -0.78539816339; // 45 deg in rads
-    let mut x1 = INVERSE_PHI * theta_min + (1.0 - INVERSE_PHI) * theta_max;
-    let mut f1 = distance_at_angle(points, template_points, x1);
-    let mut x2 = (1.0 - INVERSE_PHI) * theta_min + INVERSE_PHI * theta_max;
-    let mut f2 = distance_at_angle(points, template_points, x2);
-
-    while (theta_max - theta_min).abs() > DELTA_THETA {
-        if f1 < f2 {
-            theta_max = x2;
-            x2 = x1;
-            f2 = f1;
-            x1 = INVERSE_PHI * theta_min + (1.0 - INVERSE_PHI) * theta_max;
-            f1 = distance_at_angle(points, template_points, x1)
-        } else {
-            theta_min = x1;
-            x1 = x2;
-            f1 = f2;
-            x2 = (1.0 - INVERSE_PHI) * theta_min + INVERSE_PHI * theta_max;
-            f2 = distance_at_angle(points, template_points, x2)
+fn main() {
+    // --pack FILE starts from a template pack and saves every change back to it (it's created when it doesn't exist),
+    // --record FILE logs every stroke, --replay FILE plays a recording back (see session.rs)
+    let mut pack_path = None;
+    let mut record = None;
+    let mut replay = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--pack" => pack_path = args.next().map(PathBuf::from),
+            "--record" => record = args.next().map(PathBuf::from),
+            "--replay" => replay = args.next().map(PathBuf::from),
+            _ => eprintln!("unknown argument {}", arg),
         }
     }
 
-    f32::min(f1, f2)
-}
-
-fn distance_at_angle(points: &Vec<Vec2>, template_points: &[Vec2; RESAMPLE_TARGET_POINTS], theta: f32) -> f32 {
-    let mut rotated_points = Vec::with_capacity(points.len());
-    let centroid = get_centroid(points);
-    let cos = ops::cos(theta);
-    let sin = ops::sin(theta);
-    for point in points.iter() {
-        let x_ = point.x - centroid.x;
-        let y_ = point.y - centroid.y;
-        rotated_points.push(Vec2::new(
-            x_ * cos + y_ * sin + centroid.x,
-            y_ * cos - x_ * sin + centroid.y,
-        ));
-    }
-    let mut path_distance = 0.0;
-    for index in 0..points.len() {
-        // squared distance is quicker; dont really care about score
-        let d = rotated_points[index].distance_squared(template_points[index]);
-        path_distance += d;
-    }
-    path_distance / (points.len() as f32).powi(2)
-}
-
-fn reset_board(window_size: Vec2, board: &mut Image, resize: bool) {
-    if resize {
-        board.resize(Extent3d {
-            width: window_size.x as u32,
-            height: window_size.y as u32,
-            depth_or_array_layers: 1,
-        });
-    }
-
-    for x in 0..(window_size.x as u32) {
-        for y in 0..(window_size.y as u32) {
-            board.set_color_at(x, y, BOARD_COLOR).unwrap_or(());
+    // a pack that doesn't load isn't started from (or saved over), the gestures in it would be lost
+    let pack = match pack_path.as_ref().filter(|path| path.exists()).map(|path| (path, read_pack(path))) {
+        Some((_, Ok(json))) => Some(json),
+        Some((path, Err(error))) => {
+            eprintln!("{}: {}", path.display(), error);
+            std::process::exit(1);
         }
-    }
-}
+        None => None,
+    };
 
-fn main() {
     App::new()
         .add_plugins((
             DefaultPlugins,
@@ -247,84 +98,58 @@ fn main() {
                     enabled: true,
                 },
             },
+            GestureRecognizerPlugin {
+                config: GestureConfig {
+                    pack,
+                    world_plane: Some(InfinitePlane3d::new(Vec3::Z)),
+                    record,
+                    replay,
+                    ..default()
+                },
+            },
         ))
-        .add_systems(Startup, (setup_window, spawn))
+        .add_systems(Startup, (setup_window, spawn, spawn_debug_text))
         .add_systems(
             Update,
-            (toggle_brush, handle_adding_gestures, draw_state_handler, draw, textbox_input_listener).chain(),
+            (
+                handle_adding_gestures,
+                handle_condensing,
+                toggle_library,
+                handle_library_buttons,
+                scroll_library,
+                cycle_backend,
+            )
+                .chain()
+                .before(GestureSystems),
         )
-        .insert_resource(IsTyping(false))
-        .insert_resource(OverAButton(false))
-        .insert_resource(ResampledPoints(Vec::new()))
-        .insert_resource(StrokeTemplates(templates::stroke_templates()))
-        .insert_resource(DrawState(DrawMoment::Idle))
-        .insert_resource(BrushEnabled(true))
+        .add_systems(
+            Update,
+            (
+                show_results,
+                log_gestures,
+                textbox_input_listener,
+                rename_input_listener,
+                handle_conflict_choice,
+                refresh_library,
+                toggle_debug_overlay,
+                draw_debug_overlay,
+                save_pack,
+            )
+                .chain()
+                .after(GestureSystems),
+        )
+        // the plugin's board is transparent, the app draws on black
+        .insert_resource(ClearColor(Color::BLACK))
+        .insert_resource(PackFile(pack_path))
+        .insert_resource(PendingGesture(None))
+        .insert_resource(LibraryOpen(false))
+        .insert_resource(DebugOverlay(false))
         .run();
 }
 
-fn toggle_brush(
-    mut brush_enabled: ResMut<BrushEnabled>,
-    mut interaction_query: Query<
-        (
-            &Interaction,
-            &mut BorderColor,
-        ),
-        (Changed<Interaction>, With<ToggleBrushButton>),
-    >,
-    mut text: Single<&mut Text, With<ToggleBrushButton>>,
-) {
-    for (interaction, mut border_color) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => {
-                brush_enabled.0 = !brush_enabled.0;
-                border_color.0 = bevy::color::palettes::css::LIGHT_GREEN.into();
-                text.0 = if brush_enabled.0 { format!("ON") } else { format!("OFF") };
-            }
-            _ => {
-                text.0 = format!("Toggle Brush");
-                border_color.0 = Color::WHITE;
-            }
-        }
-    }
-}
-
-fn draw_state_handler(
-    buttons: Res<ButtonInput<MouseButton>>,
-    touches: Res<Touches>,
-    mouse_move_delta: Res<bevy::input::mouse::AccumulatedMouseMotion>,
-    mut draw_state: ResMut<DrawState>,
-    window: Single<&Window>,
-) {
-    if buttons.just_pressed(MouseButton::Left) {
-        if let Some(x) = window.cursor_position() {
-            draw_state.0 = DrawMoment::InputBegan(x);
-        }
-    } else if buttons.pressed(MouseButton::Left) && mouse_move_delta.delta != Vec2::ZERO {
-        if let Some(x) = window.cursor_position() {
-            draw_state.0 = DrawMoment::Held(x);
-        }
-    } else {
-        draw_state.0 = DrawMoment::Idle;
-        
-        for touch in touches.iter() {
-            if touches.just_pressed(touch.id()) {
-                draw_state.0 = DrawMoment::InputBegan(touch.position());
-            } else if touch.delta() != Vec2::ZERO {
-                draw_state.0 = DrawMoment::Held(touch.position());
-            }
-            break;
-        }
-    }
-
-    if buttons.just_released(MouseButton::Left) || touches.any_just_released() {
-        draw_state.0 = DrawMoment::InputEnded;
-    }
-}
-
 fn handle_adding_gestures(
     mut commands: Commands,
-    mut typing: ResMut<IsTyping>,
-    mut over_button: ResMut<OverAButton>,
+    mut capture: ResMut<GestureCapture>,
     mut interaction_query: Query<
         (
             &Interaction,
@@ -337,10 +162,10 @@ fn handle_adding_gestures(
     for (interaction, mut border_color) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
-                over_button.0 = true;
+                capture.skip_frame = true;
                 border_color.0 = bevy::color::palettes::css::LIGHT_GREEN.into();
-                if !result_text.0.is_empty() && !typing.0 {
-                    typing.0 = true;
+                if !result_text.0.is_empty() && !capture.paused {
+                    capture.paused = true;
                     commands
                         .spawn(Node {
                             width: Val::Percent(100.0),
@@ -364,6 +189,7 @@ fn handle_adding_gestures(
                                     font_size: 34.,
                                     ..default()
                                 }),
+                                GestureNameInput,
                             ));
                         });
                 }
@@ -375,125 +201,309 @@ fn handle_adding_gestures(
     }
 }
 
+fn handle_condensing(
+    mut capture: ResMut<GestureCapture>,
+    mut custom_templates: ResMut<StrokeTemplates>,
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &mut BorderColor,
+        ),
+        (Changed<Interaction>, With<CondenseButton>),
+    >,
+    mut result_text: Single<&mut Text, With<ResultText>>,
+) {
+    for (interaction, mut border_color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                capture.skip_frame = true;
+                border_color.0 = bevy::color::palettes::css::LIGHT_GREEN.into();
+
+                // a few synthetic strokes per template to see what the condensing costs; seeded so pressing twice reports the same
+                let mut rng = StdRng::seed_from_u64(0);
+                let evaluation = synthetic::dataset(&custom_templates.0, 3, &SyntheticConfig::default(), &mut rng);
+                let (condensed, report) = condense(&custom_templates.0, &CondenseOptions::default(), &evaluation);
+                // the same strokes through the feature classifier, to compare the two backends
+                let rubine_accuracy = |templates| {
+                    RubineClassifier::train_with_synthetic(templates).map_or(0.0, |rubine| rubine.accuracy(&evaluation))
+                };
+                let rubine_before = rubine_accuracy(&custom_templates.0);
+                let rubine_after = rubine_accuracy(&condensed);
+                custom_templates.0 = condensed;
+
+                result_text.0 = format!(
+                    "{} -> {} templates ({} merged, {} removed)\nleave-one-out accuracy {:.0}% -> {:.0}%\nsynthetic accuracy {:.0}% -> {:.0}%\nRubine synthetic accuracy {:.0}% -> {:.0}%",
+                    report.templates_before,
+                    report.templates_after,
+                    report.merged,
+                    report.removed,
+                    report.accuracy_before * 100.0,
+                    report.accuracy_after * 100.0,
+                    report.evaluation_accuracy_before.unwrap_or_default() * 100.0,
+                    report.evaluation_accuracy_after.unwrap_or_default() * 100.0,
+                    rubine_before * 100.0,
+                    rubine_after * 100.0,
+                );
+            }
+            _ => {
+                border_color.0 = Color::WHITE;
+            }
+        }
+    }
+}
+
+// F4 cycles through the template matcher, the feature classifier and the DTW matcher
+fn cycle_backend(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut config: ResMut<GestureConfig>,
+    mut result_text: Single<&mut Text, With<ResultText>>,
+) {
+    if keys.just_pressed(KeyCode::F4) {
+        config.backend = match config.backend {
+            Backend::Dollar1 => Backend::Rubine,
+            Backend::Rubine => Backend::Dtw,
+            Backend::Dtw => Backend::Dollar1,
+        };
+        result_text.0 = format!("Recognizing with {:?}", config.backend);
+    }
+}
+
 fn textbox_input_listener(
     mut events: EventReader<TextInputSubmitEvent>,
-    mut typing: ResMut<IsTyping>,
+    mut capture: ResMut<GestureCapture>,
     mut commands: Commands,
-    resampled_points: Res<ResampledPoints>,
+    last_stroke: Res<LastStroke>,
     mut custom_templates: ResMut<StrokeTemplates>,
+    mut pending_gesture: ResMut<PendingGesture>,
+    name_inputs: Query<(), With<GestureNameInput>>,
     mut result_text: Single<&mut Text, With<ResultText>>,
 ) {
     for event in events.read() {
+        if !name_inputs.contains(event.entity) {
+            continue;
+        }
         let text = &event.value;
-        
-        if resampled_points.0.len() == RESAMPLE_TARGET_POINTS {
-            if let Some(set) = custom_templates.0.get_mut(text) {
-                set.insert(Template(resampled_points.0.to_owned().try_into().unwrap()));
-            } else {
-                custom_templates.0.insert(text.clone(), HashSet::from([Template(resampled_points.0.to_owned().try_into().unwrap())]));
-            }
-            result_text.0 = format!("{} gesture added!", text);
-        } else {
+        commands.entity(event.entity).despawn();
+
+        let Some(mut template) = to_template(&last_stroke.normalized) else {
             result_text.0 = format!("Gesture drawn has too little resampled points (< {})", RESAMPLE_TARGET_POINTS);
+            capture.paused = false;
+            continue;
+        };
+        template.stroke = last_stroke.stroke.clone();
+
+        // see if the stroke would rather be recognized as some other gesture before it goes in
+        let distances = class_distances(&last_stroke.normalized, &custom_templates.0);
+        let own_distance = distances.iter().find(|(name, _)| name == text).map(|(_, d)| *d);
+        let conflict = distances.iter().find(|(name, _)| name != text).filter(|(_, d)| match own_distance {
+            Some(own_distance) => *d < own_distance,
+            None => *d < CONFLICT_DISTANCE,
+        });
+
+        if let Some((other, other_distance)) = conflict {
+            let warning = match own_distance {
+                Some(own_distance) => format!(
+                    "This stroke is closer to {} ({:.2}) than to {} ({:.2})",
+                    other, other_distance, text, own_distance
+                ),
+                None => format!(
+                    "This stroke is very close to {} ({:.2}), {} will get confused with it",
+                    other, other_distance, text
+                ),
+            };
+            spawn_conflict_dialog(&mut commands, warning);
+            pending_gesture.0 = Some((text.clone(), template));
+            // capture stays paused, the dialog has to be answered first
+        } else {
+            add_template(&mut custom_templates.0, text, template);
+            result_text.0 = format!("{} gesture added!", text);
+            capture.paused = false;
         }
-        
-        typing.0 = false;
-        commands.entity(event.entity).despawn();
     }
 }
 
-fn fill_pixel(board: &mut Image, vec: Vec2, first_pixel: bool, brush_enabled: bool) {
-    let thickness = if first_pixel { BRUSH_THICKNESS*2 } else { BRUSH_THICKNESS };
-    if brush_enabled {
-        for theta in 0..=360 {
-            for delta_r in 0..=thickness {
-                let x = vec.x + (delta_r as f32) * ops::cos((theta as f32).to_radians());
-                let y = vec.y + (delta_r as f32) * ops::sin((theta as f32).to_radians());
-                board
-                    .set_color_at(x as u32, y as u32, BRUSH_COLOR)
-                    .unwrap_or(()); // most likely the error would be an out_of_bounds so it i think im okay to ignore
-            }
-        }
-    } else {
-        board
-            .set_color_at(vec.x as u32, vec.y as u32, BRUSH_COLOR)
-            .unwrap_or(()); // most likely the error would be an out_of_bounds so it i think im okay to ignore
-    }
+fn add_template(templates: &mut HashMap<String, HashSet<Template>>, name: &str, template: Template) {
+    templates.entry(name.to_string()).or_default().insert(template);
 }
 
-fn draw(
+fn handle_conflict_choice(
+    mut commands: Commands,
+    mut capture: ResMut<GestureCapture>,
+    mut pending_gesture: ResMut<PendingGesture>,
+    mut custom_templates: ResMut<StrokeTemplates>,
+    interaction_query: Query<(&Interaction, &ConflictChoice), Changed<Interaction>>,
+    dialog: Query<Entity, With<ConflictDialog>>,
     mut result_text: Single<&mut Text, With<ResultText>>,
-    drawingboard: Res<DrawingBoard>,
-    mut images: ResMut<Assets<Image>>,
-
-    window: Single<&Window>,
-
-    is_typing: Res<IsTyping>,
-    mut over_button: ResMut<OverAButton>,
-    custom_templates: Res<StrokeTemplates>,
-    mut final_resampled_points: ResMut<ResampledPoints>,
-    mut previous_pos: Local<Vec2>,
-    mut candidate_points: Local<Vec<Vec2>>,
-    mut total_length: Local<f32>,
-
-    mut draw_state: ResMut<DrawState>,
-    brush_enabled: Res<BrushEnabled>,
 ) {
-    if is_typing.0 || over_button.0 {
-        draw_state.0 = DrawMoment::Idle;
-        over_button.0 = false;
-        return;
-    }
-    if let DrawMoment::InputBegan(mouse_pos) = draw_state.0 {
-        candidate_points.clear();
-        *total_length = 0.0;
-        result_text.0 = "".to_string();
+    for (interaction, choice) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        capture.skip_frame = true;
 
-        let board = images.get_mut(&drawingboard.0).expect("Board not found!!");
-        reset_board(window.size(), board, true);
+        if let Some((name, template)) = pending_gesture.0.take() {
+            match choice {
+                ConflictChoice::AddAnyway => {
+                    add_template(&mut custom_templates.0, &name, template);
+                    result_text.0 = format!("{} gesture added!", name);
+                }
+                ConflictChoice::Cancel => {
+                    result_text.0 = format!("{} gesture not added", name);
+                }
+            }
+        }
 
-        fill_pixel(board, mouse_pos, true, brush_enabled.0);
-        *previous_pos = mouse_pos;
-        candidate_points.push(mouse_pos);
-    } else if draw_state.0 == DrawMoment::InputEnded {
-        let start_time = Utc::now();
+        for entity in &dialog {
+            commands.entity(entity).despawn_recursive();
+        }
+        capture.paused = false;
+    }
+}
 
-        let mut resampled_points = resample(*total_length, &candidate_points);
-        rotate_about_centroid(&mut resampled_points);
-        scale_and_translate(&mut resampled_points);
-        let (shape, _least_path_squared) = recognize(&resampled_points, custom_templates);
+fn spawn_conflict_dialog(commands: &mut Commands, warning: String) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(15.0),
+                bottom: Val::Px(300.0),
+                ..default()
+            },
+            ConflictDialog,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(warning),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(bevy::color::palettes::css::ORANGE.into()),
+            ));
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(15.0),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (label, choice) in [("Add anyway", ConflictChoice::AddAnyway), ("Cancel", ConflictChoice::Cancel)] {
+                        parent
+                            .spawn((
+                                Button,
+                                Node {
+                                    width: Val::Px(140.0),
+                                    height: Val::Px(65.0),
+                                    border: UiRect::all(Val::Px(3.0)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                BorderColor(Color::WHITE),
+                                BorderRadius::MAX,
+                                BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                                choice,
+                            ))
+                            .with_child((
+                                Text::new(label),
+                                TextFont {
+                                    font_size: 17.0,
+                                    ..default()
+                                },
+                                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                            ));
+                    }
+                });
+        });
+}
 
-        let end_time = Utc::now();
-        let elapsed_time = end_time.signed_duration_since(start_time);
+// the result text follows the board: cleared when a new board starts, the provisional guess while drawing,
+// and every stroke's gesture once they're finished
+fn show_results(
+    mut recognized: EventReader<GestureRecognized>,
+    mut rejected: EventReader<GestureRejected>,
+    board_strokes: Res<BoardStrokes>,
+    last_stroke: Res<LastStroke>,
+    provisional_guess: Res<ProvisionalGuess>,
+    mut result_text: Single<&mut Text, With<ResultText>>,
+) {
+    let finished = recognized.read().count() + rejected.read().count() > 0;
+    if finished {
+        // with more than one finger every stroke gets its own line, in the order they were started
+        let shapes = if board_strokes.strokes.len() > 1 {
+            board_strokes
+                .results
+                .iter()
+                .enumerate()
+                .map(|(i, shape)| format!("{}: {}", i + 1, shape))
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            board_strokes.results.join("\n")
+        };
+        // how the stroke that just ended was drawn
+        let timing = match last_stroke.temporal {
+            Some(temporal) => format!(
+                "\n{:.2} s, {:.0} px/s average, {:.0} px/s peak, {} pause(s)",
+                temporal.duration, temporal.average_velocity, temporal.peak_velocity, temporal.pauses
+            ),
+            None => String::new(),
+        };
+        let elapsed_time = last_stroke.recognition_time;
         result_text.0 = format!(
-            "{}\n{}.{} milliseconds",
-            shape,
+            "{}{}\n{}.{} milliseconds",
+            shapes,
+            timing,
             elapsed_time.num_milliseconds(),
             elapsed_time.num_microseconds().get_or_insert_default()
         );
-        final_resampled_points.0 = resampled_points;
-        draw_state.0 = DrawMoment::Idle;
-    } else if let DrawMoment::Held(mouse_pos) = draw_state.0 {
-        let board = images.get_mut(&drawingboard.0).expect("Board not found!!");
-        let delta = previous_pos.distance(mouse_pos);
-
-        if delta > 6.0 {
-            let num_steps = (delta / BRUSH_THICKNESS as f32).ceil() as u32;
-            for step in 0..=num_steps {
-                let alpha = step as f32 / num_steps as f32;
-                let dv = previous_pos.lerp(mouse_pos, alpha);
-                fill_pixel(board, dv, false, brush_enabled.0);
-            }
-        } else {
-            fill_pixel(board, mouse_pos, false, brush_enabled.0);
-        }
+    } else if let Some(guess) = provisional_guess.0.as_ref().filter(|_| provisional_guess.is_changed()) {
+        result_text.0 = format!(
+            "{}? {:.0}% sure, {:.0}% drawn",
+            guess.name,
+            guess.confidence * 100.0,
+            guess.progress * 100.0
+        );
+    } else if provisional_guess.0.is_none()
+        && board_strokes.is_changed()
+        && board_strokes.results.is_empty()
+        && !board_strokes.strokes.is_empty()
+    {
+        result_text.0 = "".to_string();
+    }
+}
 
-        candidate_points.push(mouse_pos);
-        *total_length += delta;
-        *previous_pos = mouse_pos;
+// an example subscriber, games would cast a spell or run a command here
+fn log_gestures(mut recognized: EventReader<GestureRecognized>, mut rejected: EventReader<GestureRejected>) {
+    for gesture in recognized.read() {
+        info!(
+            "recognized {} (score {:.3}, angle {:.1} deg, {} points around {})",
+            gesture.name,
+            gesture.score,
+            gesture.angle.to_degrees(),
+            gesture.points.len(),
+            gesture.centroid
+        );
+        if let Some(world) = &gesture.world {
+            info!(
+                "  in the world around {}, {}",
+                world.centroid,
+                if world.closed { "closed" } else { "open" }
+            );
+        }
+    }
+    for gesture in rejected.read() {
+        match &gesture.closest {
+            Some((name, score)) => info!("rejected, closest was {} (score {:.3})", name, score),
+            None => info!("rejected, there are no gestures to match"),
+        }
     }
 }
-fn spawn(window: Single<&Window>, mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+
+fn spawn(mut commands: Commands) {
     commands.spawn(Camera2d);
     commands.spawn((
         Text::new(""),
@@ -511,7 +521,7 @@ fn spawn(window: Single<&Window>, mut commands: Commands, mut images: ResMut<Ass
     ));
     commands.spawn((
         Text::new(
-            "Misrecognized? 'Add' stroke as a gesture\n\n\n'Toggle Brush' for performance",
+            "F3 shows what the recognizer did with the stroke\nF4 switches between the $1, Rubine and DTW recognizers\n\n\nSee and edit every gesture in the 'Library'\n\n\nToo many gestures added? 'Condense' them\n\n\nMisrecognized? 'Add' stroke as a gesture",
         ),
         TextFont {
             font_size: 20.0,
@@ -548,16 +558,15 @@ fn spawn(window: Single<&Window>, mut commands: Commands, mut images: ResMut<Ass
                     BorderColor(Color::WHITE),
                     BorderRadius::MAX,
                     BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
-                    ToggleBrushButton
+                    AddGestureButton
                 ))
                 .with_child((
-                    Text::new("Toggle Brush"),
+                    Text::new("Add"),
                     TextFont {
                         font_size: 17.0,
                         ..default()
                     },
                     TextColor(Color::srgb(0.9, 0.9, 0.9)),
-                    ToggleBrushButton
                 ));
         });
     commands
@@ -583,10 +592,44 @@ fn spawn(window: Single<&Window>, mut commands: Commands, mut images: ResMut<Ass
                     BorderColor(Color::WHITE),
                     BorderRadius::MAX,
                     BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
-                    AddGestureButton
+                    CondenseButton
                 ))
                 .with_child((
-                    Text::new("Add"),
+                    Text::new("Condense"),
+                    TextFont {
+                        font_size: 17.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                ));
+        });
+    commands
+        .spawn(Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::End,
+            bottom: Val::Px(160.0),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(140.0),
+                        height: Val::Px(65.0),
+                        border: UiRect::all(Val::Px(3.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderColor(Color::WHITE),
+                    BorderRadius::MAX,
+                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                    LibraryButton
+                ))
+                .with_child((
+                    Text::new("Library"),
                     TextFont {
                         font_size: 17.0,
                         ..default()
@@ -594,24 +637,27 @@ fn spawn(window: Single<&Window>, mut commands: Commands, mut images: ResMut<Ass
                     TextColor(Color::srgb(0.9, 0.9, 0.9)),
                 ));
         });
-    let image = Image::new_fill(
-        Extent3d {
-            width: window.size().x as u32,
-            height: window.size().y as u32,
-            depth_or_array_layers: 1,
-        },
-        TextureDimension::D2,
-        &(BOARD_COLOR.to_srgba().to_u8_array()),
-        TextureFormat::Rgba8UnormSrgb,
-        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
-    );
-
-    let handle = images.add(image);
-    commands.spawn(Sprite::from_image(handle.clone()));
-    commands.insert_resource(DrawingBoard(handle));
 }
 
 fn setup_window(mut window: Single<&mut Window>) {
     window.title = String::from("$1 Unistroke Pattern Recognizer");
     window.position = WindowPosition::Centered(MonitorSelection::Current);
 }
+
+fn read_pack(path: &Path) -> Result<String, String> {
+    let json = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    pack::from_json(&json).map_err(|error| error.to_string())?;
+    Ok(json)
+}
+
+// added, renamed, deleted or condensed gestures end up in the pack right away, the app can be closed any time;
+// not the templates of a recording being replayed, the pack gets the user's back when it's done
+fn save_pack(pack_file: Res<PackFile>, custom_templates: Res<StrokeTemplates>, replay: Res<Replay>) {
+    let saved = custom_templates.is_changed() && !custom_templates.is_added() && !replay.active();
+    let Some(path) = pack_file.0.as_ref().filter(|_| saved) else {
+        return;
+    };
+    if let Err(error) = fs::write(path, pack::to_json(&custom_templates.0)) {
+        warn!("templates not saved to {} ({})", path.display(), error);
+    }
+}
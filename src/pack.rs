@@ -0,0 +1,150 @@
+// template packs: every gesture with its templates (and the strokes they were drawn with) as json,
+// so added gestures can be saved, shared between apps and loaded back in
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::recognizer::RESAMPLE_TARGET_POINTS;
+use crate::stroke::StrokePoint;
+use crate::templates::Template;
+use crate::temporal::SpeedRange;
+
+const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct PackFile {
+    version: u32,
+    gestures: BTreeMap<String, Vec<TemplateFile>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TemplateFile {
+    points: Vec<[f32; 2]>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    stroke: Vec<StrokePointFile>,
+    // [min, max] average velocity in px/s
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speed: Option<[f32; 2]>,
+}
+
+// how a raw stroke point is written, also by session recordings
+#[derive(Serialize, Deserialize)]
+pub(crate) struct StrokePointFile {
+    x: f32,
+    y: f32,
+    t: f32,
+    #[serde(default = "full_pressure")]
+    pressure: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tilt: Option<f32>,
+}
+
+fn full_pressure() -> f32 {
+    1.0
+}
+
+impl From<&StrokePoint> for StrokePointFile {
+    fn from(point: &StrokePoint) -> Self {
+        Self {
+            x: point.pos.x,
+            y: point.pos.y,
+            t: point.t,
+            pressure: point.pressure,
+            tilt: point.tilt,
+        }
+    }
+}
+
+impl From<&StrokePointFile> for StrokePoint {
+    fn from(point: &StrokePointFile) -> Self {
+        Self {
+            pos: Vec2::new(point.x, point.y),
+            t: point.t,
+            pressure: point.pressure,
+            tilt: point.tilt,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PackError {
+    Json(serde_json::Error),
+    UnsupportedVersion(u32),
+    PointCount { gesture: String, found: usize },
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackError::Json(error) => write!(f, "invalid template pack: {}", error),
+            PackError::UnsupportedVersion(version) => write!(f, "unsupported template pack version {}", version),
+            PackError::PointCount { gesture, found } => write!(
+                f,
+                "a template of {} has {} points, templates need {}",
+                gesture, found, RESAMPLE_TARGET_POINTS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+impl From<serde_json::Error> for PackError {
+    fn from(error: serde_json::Error) -> Self {
+        PackError::Json(error)
+    }
+}
+
+pub fn to_json(templates: &HashMap<String, HashSet<Template>>) -> String {
+    let gestures = templates
+        .iter()
+        .map(|(name, set)| {
+            // in a fixed order, so saving the same templates always writes the same file
+            let templates = crate::templates::sorted(set)
+                .into_iter()
+                .map(|template| TemplateFile {
+                    points: template.points.iter().map(|point| point.to_array()).collect(),
+                    stroke: template.stroke.iter().map(StrokePointFile::from).collect(),
+                    speed: template.speed.map(|speed| [speed.min, speed.max]),
+                })
+                .collect();
+            (name.clone(), templates)
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&PackFile {
+        version: VERSION,
+        gestures,
+    })
+    .expect("template packs are always serializable")
+}
+
+pub fn from_json(json: &str) -> Result<HashMap<String, HashSet<Template>>, PackError> {
+    let pack: PackFile = serde_json::from_str(json)?;
+    if pack.version != VERSION {
+        return Err(PackError::UnsupportedVersion(pack.version));
+    }
+
+    let mut templates = HashMap::new();
+    for (name, files) in pack.gestures {
+        let mut set = HashSet::new();
+        for file in files {
+            let points: Vec<Vec2> = file.points.iter().map(|point| Vec2::from_array(*point)).collect();
+            let points: [Vec2; RESAMPLE_TARGET_POINTS] = points.try_into().map_err(|points: Vec<Vec2>| {
+                PackError::PointCount {
+                    gesture: name.clone(),
+                    found: points.len(),
+                }
+            })?;
+            let stroke = file.stroke.iter().map(StrokePoint::from).collect();
+            let mut template = Template::with_stroke(points, stroke);
+            template.speed = file.speed.map(|[min, max]| SpeedRange { min, max });
+            set.insert(template);
+        }
+        templates.insert(name, set);
+    }
+    Ok(templates)
+}
@@ -0,0 +1,918 @@
+// everything it takes to get gestures out of a bevy app: capturing strokes from the mouse and touch screen,
+// recognizing them against StrokeTemplates, drawing them on a board, and sending the results as events
+// add GestureRecognizerPlugin::default() and read GestureRecognized events, the rest is optional
+
+// bevy systems take lots of params and long queries, clippy does not like either
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::f32::consts::{PI, TAU};
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::mouse::AccumulatedMouseMotion;
+use bevy::input::touch::{ForceTouch, Touch};
+use bevy::prelude::*;
+use bevy::render::{
+    render_asset::RenderAssetUsages,
+    render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy::window::{WindowResized, WindowScaleFactorChanged};
+use chrono::{TimeDelta, Utc};
+
+use crate::brush;
+use crate::pack;
+use crate::recognizer::{best_match_by, normalize_with_transform, Backend, Normalization, REJECT_DISTANCE};
+use crate::rubine::RubineClassifier;
+use crate::session::{read_session, Outcome, RecordedStroke, SessionEvent, SessionWriter};
+use crate::stroke::{positions, StrokePoint};
+use crate::streaming::{Guess, PrefixIndex};
+use crate::templates::{self, Template};
+use crate::temporal::{temporal_features, TemporalFeatures};
+
+pub const BRUSH_RADIUS: f32 = 3.0;
+pub const MATCH_RADIUS: f32 = 1.5;
+pub const BRUSH_COLOR: Color = Color::linear_rgb(255.0, 255.0, 255.0);
+// the board is see-through, only the strokes are drawn over the game
+pub const BOARD_COLOR: Color = Color::NONE;
+pub const MATCH_COLOR: Color = Color::linear_rgb(255.0, 80.0, 0.0);
+const GUESS_COLOR: Color = Color::srgba(0.0, 0.8, 1.0, 0.6);
+// how far past the camera's near plane the guess is drawn, so it's in front of the whole scene
+const GUESS_DEPTH: f32 = 0.01;
+// a stroke is guessed at again every this many new points
+const GUESS_EVERY: usize = 4;
+// a stroke is a closed shape when its ends are at most this much of its length apart
+const CLOSED_GAP: f32 = 0.2;
+
+#[derive(Resource, Clone)]
+pub struct GestureConfig {
+    pub mouse: Option<MouseButton>, // the button that draws, None ignores the mouse
+    pub touch: bool,
+    pub pack: Option<String>, // a template pack (pack::to_json) to start with, the built-in gestures when None
+    pub backend: Backend,
+    // strokes further than this from every template are rejected
+    pub reject_distance: f32,
+    // strokes are guessed at while they're drawn once they're this long (in px), None turns the guessing off
+    pub guess_length: Option<f32>,
+    pub render: bool, // draw the strokes (and what they matched) over the window, on a transparent board
+    // project finished strokes through the camera onto this plane (through the world origin) and report them
+    // in world space too; InfinitePlane3d::new(Vec3::Z) is what a 2D camera looks at, None skips the projection
+    pub world_plane: Option<InfinitePlane3d>,
+    // log every finished stroke (and the templates it was matched against) to this file, see session.rs
+    pub record: Option<PathBuf>,
+    // feed the strokes of a recording back through the capture as they were timed, and report any that come out different
+    pub replay: Option<PathBuf>,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            mouse: Some(MouseButton::Left),
+            touch: true,
+            pack: None,
+            backend: Backend::Dollar1,
+            reject_distance: REJECT_DISTANCE,
+            guess_length: Some(40.0),
+            render: true,
+            world_plane: None,
+            record: None,
+            replay: None,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct GestureRecognizerPlugin {
+    pub config: GestureConfig,
+}
+
+// the plugin's systems, apps that touch GestureCapture or StrokeTemplates order their systems around it
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GestureSystems;
+
+impl Plugin for GestureRecognizerPlugin {
+    fn build(&self, app: &mut App) {
+        let templates = match &self.config.pack {
+            Some(json) => pack::from_json(json).unwrap_or_else(|error| {
+                warn!("template pack not loaded ({}), using the built-in gestures", error);
+                templates::stroke_templates()
+            }),
+            None => templates::stroke_templates(),
+        };
+
+        let recorder = self.config.record.as_ref().and_then(|path| match File::create(path) {
+            Ok(file) => Some(SessionWriter::new(BufWriter::new(file))),
+            Err(error) => {
+                warn!("not recording to {} ({})", path.display(), error);
+                None
+            }
+        });
+        let replay = self.config.replay.as_ref().and_then(|path| {
+            let events = fs::read_to_string(path)
+                .map_err(|error| error.to_string())
+                .and_then(|text| read_session(&text).map_err(|error| error.to_string()));
+            match events {
+                Ok(events) => Some(Replay {
+                    events: events.into(),
+                    ..default()
+                }),
+                Err(error) => {
+                    warn!("not replaying {} ({})", path.display(), error);
+                    None
+                }
+            }
+        });
+
+        app.insert_resource(self.config.clone())
+            .insert_resource(StrokeTemplates(templates))
+            .insert_resource(GestureCapture::default())
+            .insert_resource(BoardStrokes::default())
+            .insert_resource(LastStroke::default())
+            .insert_resource(ProvisionalGuess::default())
+            .insert_resource(DrawState::default())
+            .insert_resource(Rubine(None))
+            .insert_resource(Prefixes(PrefixIndex::new(&HashMap::new())))
+            .insert_resource(Recorder(recorder))
+            .insert_resource(replay.unwrap_or_default())
+            .add_event::<GestureRecognized>()
+            .add_event::<GestureRejected>()
+            .add_systems(Startup, spawn_board)
+            .add_systems(
+                Update,
+                (
+                    draw_state_handler,
+                    replay_session,
+                    train_rubine,
+                    index_prefixes,
+                    record_templates,
+                    resize_board,
+                    draw,
+                    check_replay,
+                    draw_provisional_guess,
+                )
+                    .chain()
+                    .in_set(GestureSystems),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct StrokeTemplates(pub HashMap<String, HashSet<Template>>);
+
+// lets the app keep strokes from being captured
+#[derive(Resource, Default)]
+pub struct GestureCapture {
+    pub paused: bool,     // nothing is captured until it's unpaused, e.g. while a text box is open
+    pub skip_frame: bool, // the press this frame was meant for something else (a button), reset every frame
+}
+
+// what is on the board in window (logical) coordinates, so it can be painted again when the board is resized
+// the board holds every stroke drawn since the last time no pointer was down, so multi-finger input stays together
+#[derive(Resource, Default)]
+pub struct BoardStrokes {
+    pub strokes: Vec<Vec<StrokePoint>>,
+    pub overlays: Vec<Vec<Vec2>>,
+    pub results: Vec<String>, // the gesture every finished stroke was recognized as
+}
+
+// everything about the last finished stroke, for whatever wants more than the events carry
+#[derive(Resource, Default)]
+pub struct LastStroke {
+    pub stroke: Vec<StrokePoint>,
+    pub normalized: Vec<Vec2>,
+    pub normalization: Option<Normalization>,
+    pub template: Option<(String, Vec<Vec2>, f32)>, // name, points, best angle
+    pub temporal: Option<TemporalFeatures>,
+    pub recognition_time: TimeDelta,
+}
+
+// marks where strokes may begin; once any surface exists, strokes starting outside all of them aren't captured
+#[derive(Component, Clone, Copy, Debug)]
+pub enum GestureSurface {
+    Ui,          // on a UI node, the node's area
+    World(Rect), // on an entity in the world, this rectangle around it (it moves, turns and scales with the entity)
+}
+
+// the camera strokes are projected through, only needed when there's more than one
+#[derive(Component, Default)]
+pub struct GestureCamera;
+
+// an entity closed shapes can select, see WorldGesture::enclosed
+#[derive(Component, Default)]
+pub struct GestureTarget;
+
+// a finished stroke in world space, when GestureConfig::world_plane is set
+#[derive(Clone, Debug)]
+pub struct WorldGesture {
+    pub points: Vec<Vec3>,
+    pub bounds: (Vec3, Vec3), // min, max
+    pub centroid: Vec3,
+    pub closed: bool,
+    pub enclosed: Vec<Entity>, // GestureTargets inside the shape, always empty when it isn't closed
+}
+
+// the best guess at the stroke still being drawn, None when nothing is being drawn
+#[derive(Resource, Default)]
+pub struct ProvisionalGuess(pub Option<Guess>);
+
+// sent for every finished stroke, so game logic can react to gestures without reading the result text
+#[derive(Event, Clone, Debug)]
+pub struct GestureRecognized {
+    pub name: String,
+    pub score: f32, // distance to the template, lower is better
+    pub angle: f32, // how far the template was turned (clockwise on screen, in rads) to line up with the stroke
+    pub bounds: Rect, // of the raw points, in window coordinates
+    pub centroid: Vec2,
+    pub points: Vec<Vec2>,
+    pub surface: Option<Entity>, // the GestureSurface it was drawn on, None when there are no surfaces
+    pub world: Option<WorldGesture>,
+    pub temporal: Option<TemporalFeatures>, // how fast it was drawn, a flick and a slow swipe can mean different things
+}
+
+// a stroke that matched nothing, or nothing closer than GestureConfig::reject_distance
+#[derive(Event, Clone, Debug)]
+pub struct GestureRejected {
+    pub closest: Option<(String, f32)>, // the gesture it was nearest to anyway, and how far (None for a click, it has no shape)
+    pub bounds: Rect,
+    pub centroid: Vec2,
+    pub points: Vec<Vec2>,
+    pub surface: Option<Entity>,
+    pub world: Option<WorldGesture>,
+}
+
+#[derive(Resource)]
+struct DrawingBoard(Handle<Image>);
+
+#[derive(Component)]
+struct Board;
+
+#[derive(PartialEq)]
+enum DrawMoment {
+    InputEnded,
+    InputBegan(StrokePoint), // t is the app's time here, strokes store it relative to when they began
+    Held(StrokePoint),
+}
+
+// the mouse and every finger on the screen draw their own stroke
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum PointerId {
+    Mouse,
+    Touch(u64),
+    Replay, // strokes from a recording
+}
+
+// whatever each pointer did this frame, pointers that did nothing are left out
+#[derive(Resource, Default)]
+struct DrawState(Vec<(PointerId, DrawMoment)>);
+
+// a stroke that is still being drawn
+struct ActiveStroke {
+    index: usize, // into BoardStrokes::strokes
+    previous_pos: Vec2,
+    total_length: f32,
+    start_time: f32,
+    guessed_at: usize, // how many points the stroke had when it was last guessed at
+    surface: Option<Entity>,
+}
+
+// retrained whenever the templates change
+#[derive(Resource)]
+struct Rubine(Option<RubineClassifier>);
+
+// the template prefixes partial strokes are matched against, rebuilt whenever the templates change
+#[derive(Resource)]
+struct Prefixes(PrefixIndex);
+
+#[derive(Resource)]
+struct Recorder(Option<SessionWriter<BufWriter<File>>>);
+
+// a recording being played back, a stroke at a time
+#[derive(Resource, Default)]
+pub struct Replay {
+    events: VecDeque<SessionEvent>,
+    started: Option<f32>,        // real time the replay began, events are due relative to it
+    expected: Option<Outcome>,   // what the stroke fed this frame was recognized as when it was recorded
+    strokes: usize,
+    different: usize,
+    // what the recording overrode, put back when it's done
+    restore: Option<(Backend, f32, HashMap<String, HashSet<Template>>)>,
+}
+
+impl Replay {
+    // while it plays the recording's templates are in place, they're not the user's to save or record
+    pub fn active(&self) -> bool {
+        self.restore.is_some()
+    }
+}
+
+#[derive(SystemParam)]
+struct GestureEvents<'w> {
+    recognized: EventWriter<'w, GestureRecognized>,
+    rejected: EventWriter<'w, GestureRejected>,
+}
+
+// the parts of the scene strokes are related to: surfaces they start on, the camera they're seen through
+// and the targets they can enclose
+#[derive(SystemParam)]
+struct Scene<'w, 's> {
+    surfaces: Query<'w, 's, (Entity, &'static GestureSurface, &'static GlobalTransform, Option<&'static ComputedNode>)>,
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform, Has<GestureCamera>)>,
+    targets: Query<'w, 's, (Entity, &'static GlobalTransform), With<GestureTarget>>,
+}
+
+impl Scene<'_, '_> {
+    // None when a stroke starting at this (window) position isn't captured,
+    // otherwise the surface it starts on (which is None when there are no surfaces at all)
+    fn surface_at(&self, position: Vec2) -> Option<Option<Entity>> {
+        if self.surfaces.is_empty() {
+            return Some(None);
+        }
+        let world_position = self
+            .camera()
+            .and_then(|(camera, transform)| camera.viewport_to_world_2d(transform, position).ok());
+
+        self.surfaces
+            .iter()
+            .find(|(_, surface, transform, node)| match (surface, node) {
+                // ui nodes are laid out in physical pixels, centered on their transform
+                (GestureSurface::Ui, Some(node)) => {
+                    let scale = node.inverse_scale_factor();
+                    Rect::from_center_size(transform.translation().truncate() * scale, node.size() * scale).contains(position)
+                }
+                (GestureSurface::Ui, None) => false,
+                (GestureSurface::World(rect), _) => world_position.is_some_and(|world_position| {
+                    let local = transform.affine().inverse().transform_point3(world_position.extend(0.0));
+                    rect.contains(local.truncate())
+                }),
+            })
+            .map(|(entity, ..)| Some(entity))
+    }
+
+    // the GestureCamera if there is one, otherwise the first active camera
+    fn camera(&self) -> Option<(&Camera, &GlobalTransform)> {
+        let active = || self.cameras.iter().filter(|(camera, ..)| camera.is_active);
+        active()
+            .find(|(.., marked)| *marked)
+            .or_else(|| active().next())
+            .map(|(camera, transform, _)| (camera, transform))
+    }
+
+    // the stroke's (window) points projected through the camera onto the plane, as it is seen right now
+    fn world_gesture(&self, points: &[Vec2], plane: InfinitePlane3d) -> Option<WorldGesture> {
+        let (camera, transform) = self.camera()?;
+        let world_points: Vec<Vec3> = points
+            .iter()
+            .filter_map(|point| {
+                let ray = camera.viewport_to_world(transform, *point).ok()?;
+                let distance = ray.intersect_plane(Vec3::ZERO, plane)?;
+                Some(ray.get_point(distance))
+            })
+            .collect();
+        let (&first, &last) = (world_points.first()?, world_points.last()?);
+
+        let (min, max) = world_points
+            .iter()
+            .fold((Vec3::MAX, Vec3::MIN), |(min, max), point| (min.min(*point), max.max(*point)));
+        let centroid = world_points.iter().sum::<Vec3>() / world_points.len() as f32;
+        let length: f32 = world_points.windows(2).map(|pair| pair[0].distance(pair[1])).sum();
+        let closed = world_points.len() > 2 && first.distance(last) <= CLOSED_GAP * length;
+
+        let enclosed = if closed {
+            // everything is flattened onto the plane, targets above or below it count where they'd land on it
+            let (u, v) = plane.normal.any_orthonormal_pair();
+            let flatten = |point: Vec3| Vec2::new(point.dot(u), point.dot(v));
+            let polygon: Vec<Vec2> = world_points.iter().map(|point| flatten(*point)).collect();
+            self.targets
+                .iter()
+                .filter(|(_, transform)| polygon_contains(&polygon, flatten(transform.translation())))
+                .map(|(entity, _)| entity)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Some(WorldGesture {
+            points: world_points,
+            bounds: (min, max),
+            centroid,
+            closed,
+            enclosed,
+        })
+    }
+}
+
+// even-odd rule, the polygon is closed between its last and first point
+fn polygon_contains(polygon: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    let mut previous = polygon[polygon.len() - 1];
+    for &current in polygon.iter() {
+        if (current.y > point.y) != (previous.y > point.y)
+            && point.x < (previous.x - current.x) * (point.y - current.y) / (previous.y - current.y) + current.x
+        {
+            inside = !inside;
+        }
+        previous = current;
+    }
+    inside
+}
+
+// everything draw() recognizes with, grouped since bevy systems can only take so many params
+#[derive(SystemParam)]
+struct Recognizers<'w> {
+    config: Res<'w, GestureConfig>,
+    templates: Res<'w, StrokeTemplates>,
+    rubine: Res<'w, Rubine>,
+    prefixes: Res<'w, Prefixes>,
+}
+
+fn spawn_board(mut commands: Commands, config: Res<GestureConfig>, window: Single<&Window>, mut images: ResMut<Assets<Image>>) {
+    if !config.render {
+        return;
+    }
+    let image = Image::new_fill(
+        Extent3d {
+            width: window.physical_size().x.max(1),
+            height: window.physical_size().y.max(1),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &(BOARD_COLOR.to_srgba().to_u8_array()),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+
+    let handle = images.add(image);
+    // a ui node covering the window over everything else, whatever the cameras are doing; clicks go through it
+    commands.spawn((
+        ImageNode::new(handle.clone()),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        GlobalZIndex(i32::MAX),
+        PickingBehavior::IGNORE,
+        Board,
+    ));
+    commands.insert_resource(DrawingBoard(handle));
+}
+
+// the board is kept at the window's physical size so it stays sharp on HiDPI screens,
+// the node stretches it over the window;
+// cursor positions are logical, so they get multiplied by the scale factor to land on the right pixel
+fn fit_board(window: &Window, board: &mut Image) {
+    let size = window.physical_size();
+    if size.x == 0 || size.y == 0 {
+        return; // minimized
+    }
+    if board.size() != size {
+        board.resize(Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        });
+    }
+}
+
+fn paint_board(board: &mut Image, board_strokes: &BoardStrokes, scale_factor: f32) {
+    brush::clear(board, BOARD_COLOR);
+    for stroke in board_strokes.strokes.iter() {
+        if let Some(first) = stroke.first() {
+            brush::stamp_disc(board, first.pos * scale_factor, BRUSH_RADIUS * 2.0 * scale_factor, BRUSH_COLOR);
+        }
+        for pair in stroke.windows(2) {
+            let radius = pressure_radius(pair[1].pressure) * scale_factor;
+            brush::stamp_line(board, pair[0].pos * scale_factor, pair[1].pos * scale_factor, radius, BRUSH_COLOR);
+        }
+    }
+
+    for overlay in board_strokes.overlays.iter() {
+        let overlay: Vec<Vec2> = overlay.iter().map(|point| *point * scale_factor).collect();
+        brush::stamp_polyline(board, &overlay, MATCH_RADIUS * scale_factor, MATCH_COLOR);
+    }
+}
+
+// pressing harder draws a thicker line, anything without pressure draws at full width
+fn pressure_radius(pressure: f32) -> f32 {
+    BRUSH_RADIUS * (0.25 + 0.75 * pressure.clamp(0.0, 1.0))
+}
+
+fn resize_board(
+    mut resized: EventReader<WindowResized>,
+    mut scale_factor_changed: EventReader<WindowScaleFactorChanged>,
+    window: Single<&Window>,
+    drawingboard: Option<Res<DrawingBoard>>,
+    mut images: ResMut<Assets<Image>>,
+    board_strokes: Res<BoardStrokes>,
+) {
+    if resized.is_empty() && scale_factor_changed.is_empty() {
+        return;
+    }
+    resized.clear();
+    scale_factor_changed.clear();
+
+    let Some(drawingboard) = drawingboard else {
+        return;
+    };
+    let board = images.get_mut(&drawingboard.0).expect("Board not found!!");
+    fit_board(&window, board);
+    paint_board(board, &board_strokes, window.scale_factor());
+}
+
+// only when it's the backend in use, it takes a while; switching to it trains it then
+fn train_rubine(
+    config: Res<GestureConfig>,
+    custom_templates: Res<StrokeTemplates>,
+    mut rubine: ResMut<Rubine>,
+    mut trained: Local<bool>,
+) {
+    if config.backend != Backend::Rubine {
+        if rubine.0.is_some() {
+            rubine.0 = None;
+        }
+        *trained = false;
+        return;
+    }
+    if custom_templates.is_changed() || !*trained {
+        rubine.0 = RubineClassifier::train_with_synthetic(&custom_templates.0);
+        *trained = true;
+    }
+}
+
+fn index_prefixes(custom_templates: Res<StrokeTemplates>, mut prefixes: ResMut<Prefixes>) {
+    if custom_templates.is_changed() {
+        prefixes.0 = PrefixIndex::new(&custom_templates.0);
+    }
+}
+
+// every template change goes in the recording before any stroke is matched against it
+fn record_templates(
+    custom_templates: Res<StrokeTemplates>,
+    time: Res<Time<Real>>,
+    mut recorder: ResMut<Recorder>,
+    replay: Res<Replay>,
+) {
+    let Some(writer) = recorder.0.as_mut().filter(|_| custom_templates.is_changed() && !replay.active()) else {
+        return;
+    };
+    let event = SessionEvent::Templates {
+        at: time.elapsed_secs(),
+        templates: custom_templates.0.clone(),
+    };
+    if let Err(error) = writer.write(&event) {
+        warn!("recording stopped ({})", error);
+        recorder.0 = None;
+    }
+}
+
+// hands the next recorded stroke to draw() once it's due, as if it was drawn all in this frame;
+// the templates and settings it was recorded with are put in place first
+fn replay_session(
+    time: Res<Time<Real>>,
+    mut replay: ResMut<Replay>,
+    mut config: ResMut<GestureConfig>,
+    mut custom_templates: ResMut<StrokeTemplates>,
+    mut draw_state: ResMut<DrawState>,
+) {
+    let Some(first_at) = replay.events.front().map(SessionEvent::at) else {
+        return;
+    };
+    let now = time.elapsed_secs();
+    let started = *replay.started.get_or_insert(now - first_at);
+
+    if replay.restore.is_none() {
+        replay.restore = Some((config.backend, config.reject_distance, custom_templates.0.clone()));
+    }
+
+    while replay.events.front().is_some_and(|event| event.at() <= now - started) {
+        match replay.events.pop_front() {
+            Some(SessionEvent::Templates { templates, .. }) => custom_templates.0 = templates,
+            Some(SessionEvent::Stroke(stroke)) => {
+                let Some((first, rest)) = stroke.stroke.split_first() else {
+                    continue;
+                };
+                config.backend = stroke.backend;
+                config.reject_distance = stroke.reject_distance;
+                // the stroke starts at time 0, so the recorded times come out of draw() unchanged
+                draw_state.0.push((PointerId::Replay, DrawMoment::InputBegan(StrokePoint { t: 0.0, ..*first })));
+                for point in rest.iter() {
+                    draw_state.0.push((PointerId::Replay, DrawMoment::Held(*point)));
+                }
+                draw_state.0.push((PointerId::Replay, DrawMoment::InputEnded));
+                replay.expected = Some(stroke.outcome);
+                break; // one a frame, so check_replay knows which stroke the results are for
+            }
+            None => {}
+        }
+    }
+}
+
+fn check_replay(
+    mut replay: ResMut<Replay>,
+    mut recognized: EventReader<GestureRecognized>,
+    mut rejected: EventReader<GestureRejected>,
+    mut config: ResMut<GestureConfig>,
+    mut custom_templates: ResMut<StrokeTemplates>,
+) {
+    // read every frame, so strokes drawn by hand in between don't get mistaken for the replayed one
+    let recognized = recognized
+        .read()
+        .last()
+        .map(|event| Outcome::new(Some((&event.name, event.score)), config.reject_distance));
+    let rejected = rejected.read().last().map(|event| {
+        Outcome::new(event.closest.as_ref().map(|(name, score)| (name.as_str(), *score)), config.reject_distance)
+    });
+
+    // a stroke fed this frame has to be recognized (or dropped, when capture was paused) before checking the next
+    if let (Some(expected), Some(replayed)) = (replay.expected.take(), recognized.or(rejected)) {
+        replay.strokes += 1;
+        if !replayed.matches(&expected) {
+            replay.different += 1;
+            warn!("replayed stroke was recorded as {}, now it's {}", expected, replayed);
+        }
+    }
+
+    if !replay.events.is_empty() {
+        return;
+    }
+    // the app goes on with the settings and templates it had before the replay
+    if let Some((backend, reject_distance, templates)) = replay.restore.take() {
+        info!("replay finished, {} of {} strokes came out different", replay.different, replay.strokes);
+        config.backend = backend;
+        config.reject_distance = reject_distance;
+        custom_templates.0 = templates;
+    }
+}
+
+fn draw_state_handler(
+    config: Res<GestureConfig>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    mouse_move_delta: Res<AccumulatedMouseMotion>,
+    time: Res<Time<Real>>,
+    mut draw_state: ResMut<DrawState>,
+    window: Single<&Window>,
+) {
+    draw_state.0.clear();
+    let now = time.elapsed_secs();
+
+    if let Some(button) = config.mouse {
+        if buttons.just_pressed(button) {
+            if let Some(x) = window.cursor_position() {
+                draw_state.0.push((PointerId::Mouse, DrawMoment::InputBegan(StrokePoint::new(x, now))));
+            }
+        } else if buttons.pressed(button) && mouse_move_delta.delta != Vec2::ZERO {
+            if let Some(x) = window.cursor_position() {
+                draw_state.0.push((PointerId::Mouse, DrawMoment::Held(StrokePoint::new(x, now))));
+            }
+        } else if buttons.just_released(button) {
+            draw_state.0.push((PointerId::Mouse, DrawMoment::InputEnded));
+        }
+    }
+
+    if !config.touch {
+        return;
+    }
+    for touch in touches.iter() {
+        let pointer = PointerId::Touch(touch.id());
+        let point = touch_point(touch, now);
+        if touches.just_pressed(touch.id()) {
+            draw_state.0.push((pointer, DrawMoment::InputBegan(point)));
+        } else if touch.delta() != Vec2::ZERO {
+            draw_state.0.push((pointer, DrawMoment::Held(point)));
+        }
+    }
+    // a canceled touch (the os took it over) still ends its stroke
+    for touch in touches.iter_just_released().chain(touches.iter_just_canceled()) {
+        draw_state.0.push((PointerId::Touch(touch.id()), DrawMoment::InputEnded));
+    }
+}
+
+// pens and force touch screens report pressure (and pens their tilt), plain touch screens report nothing
+fn touch_point(touch: &Touch, now: f32) -> StrokePoint {
+    let mut point = StrokePoint::new(touch.position(), now);
+    match touch.force() {
+        Some(ForceTouch::Calibrated {
+            force,
+            max_possible_force,
+            altitude_angle,
+        }) => {
+            if max_possible_force > 0.0 {
+                point.pressure = (force / max_possible_force) as f32;
+            }
+            point.tilt = altitude_angle.map(|angle| angle as f32);
+        }
+        Some(ForceTouch::Normalized(force)) => point.pressure = force as f32,
+        None => {}
+    }
+    point
+}
+
+fn draw(
+    drawingboard: Option<Res<DrawingBoard>>,
+    mut images: ResMut<Assets<Image>>,
+    window: Single<&Window>,
+    mut capture: ResMut<GestureCapture>,
+    recognizers: Recognizers,
+    mut board_strokes: ResMut<BoardStrokes>,
+    mut active_strokes: Local<HashMap<PointerId, ActiveStroke>>,
+    mut draw_state: ResMut<DrawState>,
+    mut last_stroke: ResMut<LastStroke>,
+    mut provisional_guess: ResMut<ProvisionalGuess>,
+    mut gesture_events: GestureEvents,
+    scene: Scene,
+    mut recorder: ResMut<Recorder>,
+    replay: Res<Replay>,
+) {
+    if capture.paused || capture.skip_frame {
+        // strokes that end in the meantime are dropped, otherwise they'd keep the board from ever clearing
+        for (pointer, moment) in draw_state.0.drain(..) {
+            if moment == DrawMoment::InputEnded {
+                active_strokes.remove(&pointer);
+                provisional_guess.0 = None;
+            }
+        }
+        capture.skip_frame = false;
+        return;
+    }
+    let config = &recognizers.config;
+    let scale_factor = window.scale_factor();
+    let mut board = match (&drawingboard, config.render) {
+        (Some(drawingboard), true) => images.get_mut(&drawingboard.0),
+        _ => None,
+    };
+
+    for (pointer, moment) in draw_state.0.drain(..) {
+        if let DrawMoment::InputBegan(point) = moment {
+            let Some(surface) = scene.surface_at(point.pos) else {
+                continue; // not on a surface, the rest of this pointer's stroke is ignored too
+            };
+            // the first pointer down starts a fresh board, the others join in
+            if active_strokes.is_empty() {
+                *board_strokes = BoardStrokes::default();
+                if let Some(board) = board.as_deref_mut() {
+                    fit_board(&window, board);
+                    brush::clear(board, BOARD_COLOR);
+                }
+            }
+
+            if let Some(board) = board.as_deref_mut() {
+                brush::stamp_disc(board, point.pos * scale_factor, BRUSH_RADIUS * 2.0 * scale_factor, BRUSH_COLOR);
+            }
+            board_strokes.strokes.push(vec![StrokePoint { t: 0.0, ..point }]);
+            active_strokes.insert(
+                pointer,
+                ActiveStroke {
+                    index: board_strokes.strokes.len() - 1,
+                    previous_pos: point.pos,
+                    total_length: 0.0,
+                    start_time: point.t,
+                    guessed_at: 0,
+                    surface,
+                },
+            );
+        } else if moment == DrawMoment::InputEnded {
+            let Some(stroke) = active_strokes.remove(&pointer) else {
+                continue;
+            };
+            let start_time = Utc::now();
+
+            let raw_points = positions(&board_strokes.strokes[stroke.index]);
+            let (resampled_points, normalization) = normalize_with_transform(stroke.total_length, &raw_points);
+            let temporal = temporal_features(&board_strokes.strokes[stroke.index]);
+            let templates = &recognizers.templates.0;
+            let best = best_match_by(
+                config.backend,
+                &resampled_points,
+                &raw_points,
+                temporal.as_ref(),
+                templates,
+                recognizers.rubine.0.as_ref(),
+            );
+            provisional_guess.0 = None;
+
+            let end_time = Utc::now();
+
+            let (min, max) = raw_points
+                .iter()
+                .fold((Vec2::MAX, Vec2::MIN), |(min, max), point| (min.min(*point), max.max(*point)));
+            let bounds = Rect::from_corners(min, max);
+            let world = config.world_plane.and_then(|plane| scene.world_gesture(&raw_points, plane));
+            let accepted = best.as_ref().filter(|best| best.distance <= config.reject_distance);
+            match accepted {
+                Some(best) => {
+                    gesture_events.recognized.send(GestureRecognized {
+                        name: best.name.to_string(),
+                        score: best.distance,
+                        angle: (normalization.indicative_angle + best.angle + PI).rem_euclid(TAU) - PI,
+                        bounds,
+                        centroid: normalization.centroid,
+                        points: raw_points.clone(),
+                        surface: stroke.surface,
+                        world,
+                        temporal,
+                    });
+                }
+                None => {
+                    gesture_events.rejected.send(GestureRejected {
+                        closest: best.as_ref().map(|best| (best.name.to_string(), best.distance)),
+                        bounds,
+                        centroid: normalization.centroid,
+                        points: raw_points.clone(),
+                        surface: stroke.surface,
+                        world,
+                    });
+                }
+            }
+            board_strokes
+                .results
+                .push(accepted.map_or("not recognized", |best| best.name).to_string());
+
+            // replayed strokes are already in a recording
+            if let Some(writer) = recorder.0.as_mut().filter(|_| !replay.active()) {
+                let event = SessionEvent::Stroke(RecordedStroke {
+                    at: stroke.start_time,
+                    backend: config.backend,
+                    reject_distance: config.reject_distance,
+                    stroke: board_strokes.strokes[stroke.index].clone(),
+                    outcome: Outcome::new(best.as_ref().map(|best| (best.name, best.distance)), config.reject_distance),
+                });
+                if let Err(error) = writer.write(&event) {
+                    warn!("recording stopped ({})", error);
+                    recorder.0 = None;
+                }
+            }
+
+            // put the template over the stroke, so you can see how the two lined up
+            if let Some(best) = accepted {
+                board_strokes.overlays.push(normalization.denormalize(&best.template.points, best.angle));
+                if let Some(board) = board.as_deref_mut() {
+                    paint_board(board, &board_strokes, scale_factor);
+                }
+            }
+
+            *last_stroke = LastStroke {
+                stroke: board_strokes.strokes[stroke.index].clone(),
+                normalized: resampled_points,
+                normalization: Some(normalization),
+                template: best.map(|best| (best.name.to_string(), best.template.points.to_vec(), best.angle)),
+                temporal,
+                recognition_time: end_time.signed_duration_since(start_time),
+            };
+        } else if let DrawMoment::Held(point) = moment {
+            // with more than one finger down there's no telling which stroke a guess would be for
+            let guessing = active_strokes.len() == 1;
+            let Some(stroke) = active_strokes.get_mut(&pointer) else {
+                continue;
+            };
+            let delta = stroke.previous_pos.distance(point.pos);
+
+            if let Some(board) = board.as_deref_mut() {
+                let radius = pressure_radius(point.pressure) * scale_factor;
+                brush::stamp_line(board, stroke.previous_pos * scale_factor, point.pos * scale_factor, radius, BRUSH_COLOR);
+            }
+
+            board_strokes.strokes[stroke.index].push(StrokePoint {
+                t: point.t - stroke.start_time,
+                ..point
+            });
+            stroke.total_length += delta;
+            stroke.previous_pos = point.pos;
+
+            // a provisional guess, so the shape can be previewed before the stroke is finished
+            let points = &board_strokes.strokes[stroke.index];
+            let long_enough = config.guess_length.is_some_and(|length| stroke.total_length >= length);
+            if guessing && long_enough && points.len() >= stroke.guessed_at + GUESS_EVERY {
+                stroke.guessed_at = points.len();
+                provisional_guess.0 = recognizers.prefixes.0.guess(&positions(points));
+            }
+        }
+    }
+}
+
+// the whole gesture the stroke is guessed to become, over the part that's drawn so far
+fn draw_provisional_guess(
+    config: Res<GestureConfig>,
+    provisional_guess: Res<ProvisionalGuess>,
+    scene: Scene,
+    mut gizmos: Gizmos,
+) {
+    let Some(guess) = provisional_guess.0.as_ref().filter(|_| config.render) else {
+        return;
+    };
+    let Some((camera, transform)) = scene.camera() else {
+        return;
+    };
+    // the guess is in window coordinates, seen through the camera it lands right under the stroke however it moves or zooms
+    gizmos.linestrip(
+        guess
+            .completion
+            .iter()
+            .filter_map(|point| camera.viewport_to_world(transform, *point).ok())
+            .map(|ray| ray.get_point(GUESS_DEPTH)),
+        GUESS_COLOR,
+    );
+}
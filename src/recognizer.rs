@@ -0,0 +1,421 @@
+// the $1 pipeline: resample -> rotate by the indicative angle -> scale & translate -> compare
+// kept out of main.rs so other things (synthetic data, tools) can run strokes through the same steps
+
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::{FRAC_PI_4, PI};
+
+use glam::Vec2;
+
+use crate::rubine::RubineClassifier;
+use crate::stroke::Sample;
+use crate::templates::Template;
+use crate::temporal::TemporalFeatures;
+
+pub const RESAMPLE_TARGET_POINTS: usize = 64;
+pub const SCALE_SIZE: f32 = 100.0;
+// strokes further than this from every template are better called unrecognized, scribbles land well above it
+pub const REJECT_DISTANCE: f32 = 10.0;
+
+pub fn path_length(points: &[Vec2]) -> f32 {
+    points.windows(2).map(|w| w[0].distance(w[1])).sum()
+}
+
+// works on bare positions as well as full StrokePoints, the times and pressures get interpolated like the positions
+pub fn resample<S: Sample>(total_length: f32, candidate_points: &[S]) -> Vec<S> {
+    let mut resampled_points = Vec::with_capacity(RESAMPLE_TARGET_POINTS);
+    resampled_points.push(candidate_points[0]);
+
+    if candidate_points.len() > 1 {
+        /*
+         distance squared would be faster but using it leads to inaccuracies with the lerping and alpha;
+         sqrting the alpha gives lesser points for some reason;
+        */
+
+        let increment = total_length / (RESAMPLE_TARGET_POINTS) as f32;
+        let mut accumulated_distance = 0.0;
+        let mut previous_point = candidate_points[0];
+
+        for &current_point in candidate_points.iter().skip(1) {
+            let mut segment_distance = previous_point.pos().distance(current_point.pos());
+
+            while accumulated_distance + segment_distance >= increment
+                && resampled_points.len() < RESAMPLE_TARGET_POINTS
+            {
+                let alpha = (increment - accumulated_distance) / segment_distance;
+                let new_point = previous_point.lerp(&current_point, alpha);
+
+                resampled_points.push(new_point);
+
+                previous_point = new_point;
+                accumulated_distance = 0.0;
+                segment_distance = previous_point.pos().distance(current_point.pos());
+            }
+
+            accumulated_distance += segment_distance;
+            previous_point = current_point;
+        }
+    }
+
+    resampled_points
+}
+
+pub fn get_centroid(points: &[Vec2]) -> Vec2 {
+    let mut sum_x = 0.0;
+    let mut sum_y = 0.0;
+    for point in points.iter() {
+        sum_x += point.x;
+        sum_y += point.y;
+    }
+    sum_x /= points.len() as f32;
+    sum_y /= points.len() as f32;
+    Vec2::new(sum_x, sum_y)
+}
+
+// returns the indicative angle it rotated by
+pub fn rotate_about_centroid(points: &mut [Vec2]) -> f32 {
+    let centroid = get_centroid(points);
+    let indicative_angle = (centroid.y - points[0].y).atan2(centroid.x - points[0].x) + PI;
+    for point in points.iter_mut() {
+        *point = rotate(*point, centroid, indicative_angle);
+    }
+    indicative_angle
+}
+
+// rotation of a point about origin formula was x = x'cosx + y'sinx and for y you add pi/2
+fn rotate(point: Vec2, about: Vec2, angle: f32) -> Vec2 {
+    let cos = angle.cos();
+    let sin = angle.sin();
+    let x_ = point.x - about.x;
+    let y_ = point.y - about.y;
+    Vec2::new(x_ * cos + y_ * sin + about.x, y_ * cos - x_ * sin + about.y)
+}
+
+// returns the scale it applied and the centroid it moved to the origin
+pub fn scale_and_translate(points: &mut [Vec2]) -> (Vec2, Vec2) {
+    // GET BOUNDING BOX CO-ORDS
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    for point in points.iter() {
+        min_x = min_x.min(point.x);
+        min_y = min_y.min(point.y);
+        max_x = max_x.max(point.x);
+        max_y = max_y.max(point.y);
+    }
+    let b_width = max_x - min_x;
+    let b_height = max_y - min_y;
+
+    // SCALING (SCALING MESSES UP STRAIGHT LINES)
+    let scale = Vec2::new(SCALE_SIZE / b_width, SCALE_SIZE / b_height);
+    for point in points.iter_mut() {
+        *point *= scale;
+    }
+
+    // TRANSLATE TO ORIGIN (offset is for debugging purposes)
+    let centroid = get_centroid(points);
+    for point in points.iter_mut() {
+        point.x += -centroid.x;
+        point.y += -centroid.y;
+    }
+
+    (scale, centroid)
+}
+
+// what the pipeline did to a stroke, so anything in the normalized space can be put back where the stroke was drawn
+#[derive(Clone, Copy, Debug)]
+pub struct Normalization {
+    pub centroid: Vec2, // of the resampled stroke, before it was rotated
+    pub indicative_angle: f32,
+    pub scale: Vec2,
+    pub offset: Vec2,
+}
+
+impl Normalization {
+    // runs points through the same steps the stroke went through, the opposite of denormalize
+    pub fn apply(&self, points: &[Vec2]) -> Vec<Vec2> {
+        points
+            .iter()
+            .map(|point| rotate(*point, self.centroid, self.indicative_angle) * self.scale - self.offset)
+            .collect()
+    }
+
+    // undoes the best angle rotation from distance_at_best_angle and then the pipeline, in reverse order
+    pub fn denormalize(&self, points: &[Vec2], best_angle: f32) -> Vec<Vec2> {
+        points
+            .iter()
+            .map(|point| {
+                let unrotated = rotate(*point, Vec2::ZERO, -best_angle);
+                let unscaled = (unrotated + self.offset) / self.scale;
+                rotate(unscaled, self.centroid, -self.indicative_angle)
+            })
+            .collect()
+    }
+}
+
+// the whole pipeline for a raw stroke, same order draw() runs it in
+pub fn normalize(candidate_points: &[Vec2]) -> Vec<Vec2> {
+    normalize_with_transform(path_length(candidate_points), candidate_points).0
+}
+
+pub fn normalize_with_transform(total_length: f32, candidate_points: &[Vec2]) -> (Vec<Vec2>, Normalization) {
+    let mut resampled_points = resample(total_length, candidate_points);
+    let centroid = get_centroid(&resampled_points);
+    let indicative_angle = rotate_about_centroid(&mut resampled_points);
+    let (scale, offset) = scale_and_translate(&mut resampled_points);
+    (
+        resampled_points,
+        Normalization {
+            centroid,
+            indicative_angle,
+            scale,
+            offset,
+        },
+    )
+}
+
+// a normalized stroke only becomes a template when the resampling produced all the points, and finite ones
+// (a stroke without length resamples to NaN, which can't be matched against or written to a pack and read back)
+pub fn to_template(points: &[Vec2]) -> Option<Template> {
+    if !points.iter().all(|point| point.is_finite()) {
+        return None;
+    }
+    points.try_into().ok().map(Template::new)
+}
+
+// what decides which gesture a stroke is
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    #[default]
+    Dollar1, // nearest template, best_match()
+    Rubine,  // the feature based classifier in rubine.rs
+    Dtw,     // nearest template, compared with dtw_at_best_angle() instead
+}
+
+impl Backend {
+    pub const ALL: [Backend; 3] = [Backend::Dollar1, Backend::Rubine, Backend::Dtw];
+
+    // how the tools (cli, server) spell it
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Dollar1 => "dollar1",
+            Backend::Rubine => "rubine",
+            Backend::Dtw => "dtw",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|backend| backend.name() == name)
+    }
+}
+
+// how far a stroke is from a template, and the rotation that distance was found at
+pub type Matcher = fn(&[Vec2], &[Vec2; RESAMPLE_TARGET_POINTS]) -> (f32, f32);
+
+pub struct Match<'a> {
+    pub name: &'a str,
+    pub template: &'a Template,
+    pub distance: f32,
+    pub angle: f32, // the rotation distance_at_best_angle settled on
+    pub temporal: Option<TemporalFeatures>,
+}
+
+pub fn best_match<'a>(points: &[Vec2], templates: &'a HashMap<String, HashSet<Template>>) -> Option<Match<'a>> {
+    best_match_timed(points, None, templates)
+}
+
+// same as best_match, but templates with a speed range only match strokes drawn within it
+// (without timing information there's nothing to check, so every template is allowed)
+pub fn best_match_timed<'a>(
+    points: &[Vec2],
+    temporal: Option<&TemporalFeatures>,
+    templates: &'a HashMap<String, HashSet<Template>>,
+) -> Option<Match<'a>> {
+    best_match_using(points, temporal, templates, search_best_angle)
+}
+
+pub fn best_match_using<'a>(
+    points: &[Vec2],
+    temporal: Option<&TemporalFeatures>,
+    templates: &'a HashMap<String, HashSet<Template>>,
+    matcher: Matcher,
+) -> Option<Match<'a>> {
+    let mut best: Option<Match> = None;
+
+    for unistroke in templates.iter() {
+        for template in unistroke.1.iter() {
+            if !template.allows(temporal) {
+                continue;
+            }
+
+            let (distance, angle) = matcher(points, &template.points);
+            if best.as_ref().is_none_or(|best| distance < best.distance) {
+                best = Some(Match {
+                    name: unistroke.0,
+                    template,
+                    distance,
+                    angle,
+                    temporal: temporal.copied(),
+                });
+            }
+        }
+    }
+
+    best
+}
+
+// the best match by whichever backend, Rubine only matches once it's been trained
+// (and looks at the stroke as it was drawn, the others only at its normalized points)
+pub fn best_match_by<'a>(
+    backend: Backend,
+    points: &[Vec2],
+    stroke: &[Vec2],
+    temporal: Option<&TemporalFeatures>,
+    templates: &'a HashMap<String, HashSet<Template>>,
+    rubine: Option<&RubineClassifier>,
+) -> Option<Match<'a>> {
+    // a click (or any stroke that never moved) normalizes to NaN, there's no shape to match
+    if !points.iter().all(|point| point.is_finite()) {
+        return None;
+    }
+    match backend {
+        Backend::Dollar1 => best_match_timed(points, temporal, templates),
+        Backend::Rubine => rubine.and_then(|rubine| rubine.best_match(points, stroke, temporal, templates)),
+        Backend::Dtw => best_match_using(points, temporal, templates, dtw_at_best_angle),
+    }
+}
+
+pub fn recognize(points: &[Vec2], templates: &HashMap<String, HashSet<Template>>) -> (String, f32) {
+    match best_match(points, templates) {
+        Some(best) => (best.name.to_string(), best.distance),
+        None => ("not recognized".to_string(), f32::MAX),
+    }
+}
+
+// the nearest template of every gesture, closest gesture first
+pub fn class_distances(points: &[Vec2], templates: &HashMap<String, HashSet<Template>>) -> Vec<(String, f32)> {
+    let mut distances: Vec<(String, f32)> = templates
+        .iter()
+        .map(|(name, set)| {
+            let nearest = set
+                .iter()
+                .map(|template| distance_at_best_angle(points, &template.points))
+                .fold(f32::MAX, f32::min);
+            (name.clone(), nearest)
+        })
+        .collect();
+    distances.sort_by(|a, b| a.1.total_cmp(&b.1));
+    distances
+}
+
+pub fn distance_at_best_angle(points: &[Vec2], template_points: &[Vec2; RESAMPLE_TARGET_POINTS]) -> f32 {
+    search_best_angle(points, template_points).0
+}
+
+// (distance, angle) at the best angle
+pub fn search_best_angle(points: &[Vec2], template_points: &[Vec2; RESAMPLE_TARGET_POINTS]) -> (f32, f32) {
+    golden_section_search(points, template_points, distance_at_angle)
+}
+
+fn golden_section_search(
+    points: &[Vec2],
+    template_points: &[Vec2; RESAMPLE_TARGET_POINTS],
+    distance_at_angle: fn(&[Vec2], &[Vec2; RESAMPLE_TARGET_POINTS], f32) -> f32,
+) -> (f32, f32) {
+    // follows the golden-section search algorithm
+    const DELTA_THETA: f32 = 0.034_906_585; // 2 deg in rads
+    const INVERSE_PHI: f32 = 0.618_034;
+
+    let mut theta_max = FRAC_PI_4; // 45 deg in rads
+    let mut theta_min = -FRAC_PI_4; // 45 deg in rads
+    let mut x1 = INVERSE_PHI * theta_min + (1.0 - INVERSE_PHI) * theta_max;
+    let mut f1 = distance_at_angle(points, template_points, x1);
+    let mut x2 = (1.0 - INVERSE_PHI) * theta_min + INVERSE_PHI * theta_max;
+    let mut f2 = distance_at_angle(points, template_points, x2);
+
+    while (theta_max - theta_min).abs() > DELTA_THETA {
+        if f1 < f2 {
+            theta_max = x2;
+            x2 = x1;
+            f2 = f1;
+            x1 = INVERSE_PHI * theta_min + (1.0 - INVERSE_PHI) * theta_max;
+            f1 = distance_at_angle(points, template_points, x1)
+        } else {
+            theta_min = x1;
+            x1 = x2;
+            f1 = f2;
+            x2 = (1.0 - INVERSE_PHI) * theta_min + INVERSE_PHI * theta_max;
+            f2 = distance_at_angle(points, template_points, x2)
+        }
+    }
+
+    if f1 < f2 {
+        (f1, x1)
+    } else {
+        (f2, x2)
+    }
+}
+
+pub fn distance_at_angle(points: &[Vec2], template_points: &[Vec2; RESAMPLE_TARGET_POINTS], theta: f32) -> f32 {
+    let rotated_points = rotate_by(points, theta);
+    let mut path_distance = 0.0;
+    for (rotated, template) in rotated_points.iter().zip(template_points.iter()) {
+        // squared distance is quicker; dont really care about score
+        path_distance += rotated.distance_squared(*template);
+    }
+    path_distance / (points.len() as f32).powi(2)
+}
+
+fn rotate_by(points: &[Vec2], theta: f32) -> Vec<Vec2> {
+    let mut rotated_points = Vec::with_capacity(points.len());
+    let centroid = get_centroid(points);
+    let cos = theta.cos();
+    let sin = theta.sin();
+    for point in points.iter() {
+        let x_ = point.x - centroid.x;
+        let y_ = point.y - centroid.y;
+        rotated_points.push(Vec2::new(
+            x_ * cos + y_ * sin + centroid.x,
+            y_ * cos - x_ * sin + centroid.y,
+        ));
+    }
+    rotated_points
+}
+
+// how far (in points) the warping path may stray from the diagonal, the Sakoe-Chiba band
+pub const DTW_BAND: usize = RESAMPLE_TARGET_POINTS / 8;
+
+// the same golden-section search over the rotation, but with dynamic time warping as the distance,
+// so a part of the gesture drawn hastily (fewer points there) can still line up with the template
+pub fn dtw_at_best_angle(points: &[Vec2], template_points: &[Vec2; RESAMPLE_TARGET_POINTS]) -> (f32, f32) {
+    golden_section_search(points, template_points, dtw_at_angle)
+}
+
+pub fn dtw_at_angle(points: &[Vec2], template_points: &[Vec2; RESAMPLE_TARGET_POINTS], theta: f32) -> f32 {
+    dtw_distance(&rotate_by(points, theta), template_points, DTW_BAND)
+}
+
+// squared distances summed along the cheapest warping path, scaled like distance_at_angle() so the scores compare
+pub fn dtw_distance(a: &[Vec2], b: &[Vec2], band: usize) -> f32 {
+    if a.is_empty() || b.is_empty() {
+        return f32::MAX;
+    }
+    // with different lengths the band follows the diagonal between the two ends
+    let band = band.max(a.len().abs_diff(b.len()));
+    let slope = b.len() as f32 / a.len() as f32;
+
+    // only two rows of the cost matrix are needed at a time
+    let mut previous = vec![f32::INFINITY; b.len() + 1];
+    let mut current = vec![f32::INFINITY; b.len() + 1];
+    previous[0] = 0.0;
+    for (i, point) in a.iter().enumerate() {
+        current.fill(f32::INFINITY);
+        let diagonal = (i as f32 * slope) as usize;
+        let from = diagonal.saturating_sub(band);
+        let to = (diagonal + band + 1).min(b.len());
+        for j in from..to {
+            let cost = point.distance_squared(b[j]);
+            current[j + 1] = cost + previous[j].min(previous[j + 1]).min(current[j]);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()] / (a.len() as f32).powi(2)
+}
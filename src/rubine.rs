@@ -0,0 +1,373 @@
+// Rubine's feature based classifier ("Specifying Gestures by Example", 1991): a stroke becomes 13 numbers
+// (11 geometric, 2 dynamic) and every gesture gets a linear function of them, the highest one wins
+// trained from the same templates the $1 matcher uses, with the features taken from the strokes as they were drawn
+// (resampled, so a shaky mouse doesn't count as turning, and scaled to the same size, so a big one isn't a different
+// gesture); the built-in templates come with their strokes turned back the way they're drawn. a pack with templates
+// that don't remember their stroke is compared normalized instead (which loses the orientation and size);
+// the dynamic features are taken from whichever templates were timed, the rest count as drawn at the average speed
+
+use std::collections::{HashMap, HashSet};
+
+use glam::Vec2;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::recognizer::{normalize, path_length, resample, search_best_angle, Match, REJECT_DISTANCE, SCALE_SIZE};
+use crate::stroke::positions;
+use crate::synthetic::{self, SyntheticConfig};
+use crate::templates::Template;
+use crate::temporal::{temporal_features, TemporalFeatures};
+
+pub const FEATURE_COUNT: usize = 13;
+// added to the diagonal of the (standardized) covariance so it can always be inverted,
+// a gesture with a single template contributes nothing to it
+const RIDGE: f32 = 0.05;
+// features that barely change over the training set (the bounding box of normalized points) are left out
+const MIN_SPREAD: f32 = 1e-3;
+// synthetic copies of every template to train with, Rubine found around 15 examples per gesture enough
+const SYNTHETIC_COPIES: usize = 15;
+// every how many of the resampled points of a drawn stroke the features are taken from, about what Rubine's mouse gave him
+// (he dropped points within 3 px of the last one); packed any closer a shaky hand adds up to a lot of turning
+const DRAWN_STEP: usize = 3;
+
+pub type Features = [f32; FEATURE_COUNT];
+
+struct Class {
+    name: String,
+    mean: Features, // standardized, what the mahalanobis distance is measured from
+    weights: Features,
+    bias: f32,
+}
+
+pub struct RubineClassifier {
+    classes: Vec<Class>,
+    // features are standardized with the training mean and spread, they range from radians to squared px/s
+    mean: Features,
+    inverse_spread: Features,
+    inverse_covariance: [[f32; FEATURE_COUNT]; FEATURE_COUNT],
+    used: usize, // features that vary over the training set
+    drawn: bool, // the features come from the strokes as drawn, not the normalized points
+    timed: bool,
+}
+
+// a stroke as drawn, evenly spaced and as big as the templates
+fn as_drawn(stroke: &[Vec2]) -> Vec<Vec2> {
+    let length = path_length(stroke);
+    if stroke.len() < 2 || !length.is_normal() {
+        return stroke.to_vec();
+    }
+    let points = resample(length, stroke);
+    let (mut min, mut max) = (Vec2::MAX, Vec2::MIN);
+    for point in points.iter() {
+        min = min.min(*point);
+        max = max.max(*point);
+    }
+    let scale = SCALE_SIZE / (max - min).max_element();
+    points.iter().step_by(DRAWN_STEP).map(|point| (*point - min) * scale).collect()
+}
+
+pub fn features(points: &[Vec2], temporal: Option<&TemporalFeatures>) -> Features {
+    let mut f = [0.0; FEATURE_COUNT];
+    if points.len() < 3 {
+        return f;
+    }
+    let first = points[0];
+    let last = points[points.len() - 1];
+
+    // f1, f2: cos and sin of the initial angle
+    let initial = points[2] - first;
+    if initial.length() > 0.0 {
+        f[0] = initial.x / initial.length();
+        f[1] = initial.y / initial.length();
+    }
+
+    // f3, f4: length and angle of the bounding box diagonal
+    let (mut min, mut max) = (Vec2::MAX, Vec2::MIN);
+    for point in points.iter() {
+        min = min.min(*point);
+        max = max.max(*point);
+    }
+    let diagonal = max - min;
+    f[2] = diagonal.length();
+    f[3] = diagonal.y.atan2(diagonal.x);
+
+    // f5, f6, f7: distance between the first and last point, and cos and sin of its angle
+    let closing = last - first;
+    f[4] = closing.length();
+    if f[4] > 0.0 {
+        f[5] = closing.x / f[4];
+        f[6] = closing.y / f[4];
+    }
+
+    // f8: path length, f9 - f11: total turning, total absolute turning and the "sharpness"
+    for window in points.windows(3) {
+        let previous = window[1] - window[0];
+        let current = window[2] - window[1];
+        f[7] += previous.length();
+        let turn = (current.x * previous.y - previous.x * current.y).atan2(current.x * previous.x + current.y * previous.y);
+        f[8] += turn;
+        f[9] += turn.abs();
+        f[10] += turn * turn;
+    }
+    f[7] += points[points.len() - 1].distance(points[points.len() - 2]);
+
+    // f12, f13: maximum speed squared and duration
+    if let Some(temporal) = temporal {
+        f[11] = temporal.peak_velocity * temporal.peak_velocity;
+        f[12] = temporal.duration;
+    }
+
+    f
+}
+
+impl RubineClassifier {
+    // None when there's nothing to train from
+    pub fn train(templates: &HashMap<String, HashSet<Template>>) -> Option<Self> {
+        let drawn = templates.values().flatten().all(|template| template.stroke.len() >= 2);
+        let timed = templates
+            .values()
+            .flatten()
+            .any(|template| temporal_features(&template.stroke).is_some());
+
+        let mut names: Vec<&String> = templates.keys().filter(|name| !templates[*name].is_empty()).collect();
+        names.sort();
+        if names.is_empty() {
+            return None;
+        }
+
+        // features, and whether the template had timing
+        let mut raw: Vec<Vec<(Features, bool)>> = names
+            .iter()
+            .map(|name| {
+                crate::templates::sorted(&templates[*name])
+                    .into_iter()
+                    .map(|template| {
+                        let points = if drawn { as_drawn(&positions(&template.stroke)) } else { template.points.to_vec() };
+                        let temporal = temporal_features(&template.stroke);
+                        (features(&points, temporal.as_ref()), temporal.is_some())
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // the untimed templates get the average timing, so it doesn't favour their gestures either way
+        let timed_examples: Vec<Features> = raw.iter().flatten().filter(|(_, timed)| *timed).map(|(f, _)| *f).collect();
+        let timed_mean = mean(&timed_examples);
+        for (f, timed) in raw.iter_mut().flatten() {
+            if !*timed {
+                f[11] = timed_mean[11];
+                f[12] = timed_mean[12];
+            }
+        }
+        let raw: Vec<Vec<Features>> = raw.into_iter().map(|class| class.into_iter().map(|(f, _)| f).collect()).collect();
+
+        let all: Vec<Features> = raw.iter().flatten().copied().collect();
+        let overall_mean = mean(&all);
+        let mut inverse_spread = [0.0; FEATURE_COUNT];
+        for (i, inverse) in inverse_spread.iter_mut().enumerate() {
+            let variance = all.iter().map(|example| (example[i] - overall_mean[i]).powi(2)).sum::<f32>() / all.len() as f32;
+            if variance.sqrt() > MIN_SPREAD {
+                *inverse = 1.0 / variance.sqrt();
+            }
+        }
+        let standardize = |f: &Features| -> Features { std::array::from_fn(|i| (f[i] - overall_mean[i]) * inverse_spread[i]) };
+
+        let examples: Vec<Vec<Features>> = raw.iter().map(|class| class.iter().map(standardize).collect()).collect();
+        let class_means: Vec<Features> = examples.iter().map(|class| mean(class)).collect();
+
+        // the covariance pooled over every gesture, each around its own mean
+        let mut covariance = [[0.0; FEATURE_COUNT]; FEATURE_COUNT];
+        for (class, class_mean) in examples.iter().zip(class_means.iter()) {
+            for example in class.iter() {
+                for i in 0..FEATURE_COUNT {
+                    for j in 0..FEATURE_COUNT {
+                        covariance[i][j] += (example[i] - class_mean[i]) * (example[j] - class_mean[j]);
+                    }
+                }
+            }
+        }
+        let degrees_of_freedom = (all.len() as f32 - names.len() as f32).max(1.0);
+        for (i, row) in covariance.iter_mut().enumerate() {
+            for value in row.iter_mut() {
+                *value /= degrees_of_freedom;
+            }
+            row[i] += RIDGE;
+        }
+        let inverse = invert(covariance)?;
+
+        let classes = names
+            .iter()
+            .zip(class_means.iter())
+            .map(|(name, class_mean)| {
+                let mut weights = [0.0; FEATURE_COUNT];
+                for (j, weight) in weights.iter_mut().enumerate() {
+                    *weight = (0..FEATURE_COUNT).map(|i| inverse[i][j] * class_mean[i]).sum();
+                }
+                Class {
+                    name: name.to_string(),
+                    mean: *class_mean,
+                    weights,
+                    bias: -0.5 * dot(&weights, class_mean),
+                }
+            })
+            .collect();
+
+        Some(Self {
+            classes,
+            mean: overall_mean,
+            inverse_spread,
+            inverse_covariance: inverse,
+            used: inverse_spread.iter().filter(|inverse| **inverse > 0.0).count(),
+            drawn,
+            timed,
+        })
+    }
+
+    // Rubine needs a handful of examples per gesture to estimate how they vary, the built-in gestures have one each;
+    // synthetic copies fill that in (seeded, so the same templates always give the same classifier)
+    pub fn train_with_synthetic(templates: &HashMap<String, HashSet<Template>>) -> Option<Self> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut enriched = templates.clone();
+        // a hand shakes less than the $1 copies are jittered, that much would read as turning to Rubine
+        let config = SyntheticConfig {
+            noise: 0.005,
+            ..SyntheticConfig::default()
+        };
+        synthetic::enrich_templates(&mut enriched, SYNTHETIC_COPIES, &config, &mut rng);
+        Self::train(&enriched)
+    }
+
+    // the standardized features of a stroke (its normalized points and its raw positions)
+    fn standardized(&self, points: &[Vec2], stroke: &[Vec2], temporal: Option<&TemporalFeatures>) -> Features {
+        let drawn;
+        let points = if self.drawn {
+            drawn = as_drawn(stroke);
+            &drawn
+        } else {
+            points
+        };
+        let mut f = features(points, if self.timed { temporal } else { None });
+        // without timing the dynamic features are taken as average, so they don't favour any gesture
+        if self.timed && temporal.is_none() {
+            f[11] = self.mean[11];
+            f[12] = self.mean[12];
+        }
+        std::array::from_fn(|i| (f[i] - self.mean[i]) * self.inverse_spread[i])
+    }
+
+    // the highest scoring of the gestures that are allowed, with Rubine's estimate of how likely it is to be right
+    fn pick(&self, f: &Features, allowed: impl Fn(&str) -> bool) -> Option<(&Class, f32)> {
+        let scores: Vec<(&Class, f32)> = self
+            .classes
+            .iter()
+            .filter(|class| allowed(&class.name))
+            .map(|class| (class, class.bias + dot(&class.weights, f)))
+            .collect();
+        let &(best, best_score) = scores.iter().max_by(|a, b| a.1.total_cmp(&b.1))?;
+        let probability = 1.0 / scores.iter().map(|(_, score)| (score - best_score).exp()).sum::<f32>();
+        Some((best, probability))
+    }
+
+    // squared mahalanobis distance of the features from the gesture's mean, Rubine rejects past half the features squared;
+    // scaled so that's where REJECT_DISTANCE is, then it can stand in for the $1 distance
+    fn distance(&self, class: &Class, f: &Features) -> f32 {
+        let offset: Features = std::array::from_fn(|i| f[i] - class.mean[i]);
+        let squared: f32 = (0..FEATURE_COUNT)
+            .map(|i| (0..FEATURE_COUNT).map(|j| offset[i] * self.inverse_covariance[i][j] * offset[j]).sum::<f32>())
+            .sum();
+        let reject_at = 0.5 * (self.used * self.used) as f32;
+        squared / reject_at * REJECT_DISTANCE
+    }
+
+    // the winning gesture for a stroke (its normalized points and its raw positions),
+    // with Rubine's estimate of how likely it is to be right
+    pub fn classify(&self, points: &[Vec2], stroke: &[Vec2], temporal: Option<&TemporalFeatures>) -> Option<(&str, f32)> {
+        let f = self.standardized(points, stroke, temporal);
+        self.pick(&f, |_| true).map(|(class, probability)| (class.name.as_str(), probability))
+    }
+
+    // lets the classifier stand in for best_match(): it picks the gesture (of the ones with a template the stroke's
+    // speed is allowed for) and scores it, the closest template of that gesture is what gets overlaid
+    pub fn best_match<'a>(
+        &self,
+        points: &[Vec2],
+        stroke: &[Vec2],
+        temporal: Option<&TemporalFeatures>,
+        templates: &'a HashMap<String, HashSet<Template>>,
+    ) -> Option<Match<'a>> {
+        let f = self.standardized(points, stroke, temporal);
+        let allowed = |name: &str| templates.get(name).is_some_and(|set| set.iter().any(|template| template.allows(temporal)));
+        let (class, _) = self.pick(&f, allowed)?;
+        let (name, set) = templates.get_key_value(&class.name)?;
+        let distance = self.distance(class, &f);
+        set.iter()
+            .filter(|template| template.allows(temporal))
+            .map(|template| (template, search_best_angle(points, &template.points)))
+            .min_by(|a, b| a.1 .0.total_cmp(&b.1 .0))
+            .map(|(template, (_, angle))| Match {
+                name,
+                template,
+                distance,
+                angle,
+                temporal: temporal.copied(),
+            })
+    }
+
+    // fraction of the (raw) strokes classified as the gesture they are labelled with, like condense::accuracy()
+    pub fn accuracy(&self, samples: &[(String, Vec<Vec2>)]) -> f32 {
+        if samples.is_empty() {
+            return 0.0;
+        }
+        let correct = samples
+            .iter()
+            .filter(|(name, points)| self.classify(&normalize(points), points, None).is_some_and(|(class, _)| class == name))
+            .count();
+        correct as f32 / samples.len() as f32
+    }
+}
+
+fn mean(examples: &[Features]) -> Features {
+    let mut mean = [0.0; FEATURE_COUNT];
+    for example in examples.iter() {
+        for (sum, value) in mean.iter_mut().zip(example.iter()) {
+            *sum += value;
+        }
+    }
+    mean.map(|sum| sum / examples.len().max(1) as f32)
+}
+
+fn dot(a: &Features, b: &Features) -> f32 {
+    a.iter().zip(b.iter()).map(|(a, b)| a * b).sum()
+}
+
+// gauss-jordan with partial pivoting, None when the matrix is singular
+fn invert(mut matrix: [[f32; FEATURE_COUNT]; FEATURE_COUNT]) -> Option<[[f32; FEATURE_COUNT]; FEATURE_COUNT]> {
+    let mut inverse = [[0.0; FEATURE_COUNT]; FEATURE_COUNT];
+    for (i, row) in inverse.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+
+    for column in 0..FEATURE_COUNT {
+        let pivot = (column..FEATURE_COUNT).max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))?;
+        if matrix[pivot][column].abs() <= f32::EPSILON * f32::EPSILON {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+
+        let divisor = matrix[column][column];
+        for j in 0..FEATURE_COUNT {
+            matrix[column][j] /= divisor;
+            inverse[column][j] /= divisor;
+        }
+        for row in 0..FEATURE_COUNT {
+            if row != column {
+                let factor = matrix[row][column];
+                for j in 0..FEATURE_COUNT {
+                    matrix[row][j] -= factor * matrix[column][j];
+                    inverse[row][j] -= factor * inverse[column][j];
+                }
+            }
+        }
+    }
+    Some(inverse)
+}
@@ -0,0 +1,264 @@
+// the recognizer as a local http service, so clients that aren't rust (web, mobile) share one gesture library
+//   POST /recognize  a stroke in any format formats.rs reads -> {"recognized", "name", "score"}
+//   POST /templates  {"name", "points"} -> the template is added and the pack file saved
+//   GET  /templates  the whole pack, as pack::to_json writes it
+// plain http/1.1 over std's TcpListener, one request per connection, enough for a handful of clients on one machine
+// pages in a browser only get in from the origins allowed with with_origins() (none by default), and posts have to be
+// application/json, so no other page can add templates behind the user's back with a plain form
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::engine::Recognizer;
+use crate::formats::parse_strokes;
+
+// bodies bigger than this are refused, a long stroke is a few hundred kB at most
+const MAX_BODY: usize = 4 * 1024 * 1024;
+// a client that stops sending (or reading) halfway is dropped after this, instead of holding its thread forever
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize)]
+struct RecognizeResponse<'a> {
+    recognized: bool,
+    // the closest gesture, also when it's too far away to count
+    name: Option<&'a str>,
+    score: Option<f32>,
+}
+
+#[derive(Serialize)]
+struct TemplatesResponse<'a> {
+    name: &'a str,
+    templates: usize, // how many the gesture has now
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: &impl Serialize) -> Self {
+        Self {
+            status,
+            body: serde_json::to_string(body).expect("responses are always serializable"),
+        }
+    }
+
+    fn error(status: u16, error: impl ToString) -> Self {
+        Self::json(status, &ErrorResponse { error: error.to_string() })
+    }
+}
+
+pub struct RecognitionService {
+    recognizer: Recognizer,
+    pack_path: Option<PathBuf>, // where added templates are saved, nowhere when None
+    origins: Vec<String>,       // web pages that may call the service, like "http://localhost:8080"
+}
+
+impl RecognitionService {
+    pub fn new(recognizer: Recognizer, pack_path: Option<PathBuf>) -> Self {
+        Self {
+            recognizer,
+            pack_path,
+            origins: Vec::new(),
+        }
+    }
+
+    pub fn with_origins(mut self, origins: Vec<String>) -> Self {
+        self.origins = origins;
+        self
+    }
+
+    pub fn handle(&mut self, method: &str, path: &str, body: &str) -> Response {
+        // the query string isn't used, but clients adding one shouldn't get a 404
+        let path = path.split('?').next().unwrap_or(path);
+        match (method, path) {
+            ("POST", "/recognize") => self.recognize(body),
+            ("POST", "/templates") => self.add_template(body),
+            ("GET", "/templates") => Response {
+                status: 200,
+                body: self.recognizer.export_pack(),
+            },
+            (_, "/recognize" | "/templates") => Response::error(405, format!("{} is not allowed on {}", method, path)),
+            _ => Response::error(404, format!("nothing at {}", path)),
+        }
+    }
+
+    fn recognize(&self, body: &str) -> Response {
+        let strokes = match parse_strokes(body) {
+            Ok(strokes) => strokes,
+            Err(error) => return Response::error(400, error),
+        };
+        let [file] = strokes.as_slice() else {
+            return Response::error(400, "send one stroke per request");
+        };
+
+        let recognition = self.recognizer.recognize(&file.stroke);
+        Response::json(
+            200,
+            &RecognizeResponse {
+                recognized: recognition.as_ref().is_some_and(|recognition| recognition.recognized),
+                name: recognition.as_ref().map(|recognition| recognition.name.as_str()),
+                score: recognition.as_ref().map(|recognition| recognition.score),
+            },
+        )
+    }
+
+    fn add_template(&mut self, body: &str) -> Response {
+        let strokes = match parse_strokes(body) {
+            Ok(strokes) => strokes,
+            Err(error) => return Response::error(400, error),
+        };
+        let [file] = strokes.as_slice() else {
+            return Response::error(400, "send one stroke per request");
+        };
+        let Some(name) = file.name.as_deref() else {
+            return Response::error(400, "templates need a name, send {\"name\": .., \"points\": [..]}");
+        };
+        let Some(count) = self.recognizer.add_template(name, &file.stroke) else {
+            return Response::error(400, "the stroke is too short to become a template");
+        };
+        if let Some(path) = &self.pack_path {
+            if let Err(error) = fs::write(path, self.recognizer.export_pack()) {
+                return Response::error(500, format!("template added but not saved to {}: {}", path.display(), error));
+            }
+        }
+
+        Response::json(200, &TemplatesResponse { name, templates: count })
+    }
+
+    // answers connections until the process ends, bind to port 0 and ask local_addr() for a free one
+    // every connection is read on its own thread, so a slow client doesn't hold up the others,
+    // requests are then handled one at a time
+    pub fn serve(&mut self, listener: &TcpListener) {
+        let origins = self.origins.clone();
+        let origins = origins.as_slice();
+        let service = Mutex::new(self);
+        thread::scope(|scope| {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    // the client is gone before it was accepted (or we're out of file descriptors for a moment)
+                    Err(error) => {
+                        eprintln!("gesture server: accepting a connection: {}", error);
+                        continue;
+                    }
+                };
+                let service = &service;
+                scope.spawn(move || {
+                    // a client hanging up halfway only loses its own request
+                    if let Err(error) = answer(service, origins, stream) {
+                        eprintln!("gesture server: {}", error);
+                    }
+                });
+            }
+        });
+    }
+}
+
+fn answer(service: &Mutex<&mut RecognitionService>, origins: &[String], stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return write_response(stream, &Response::error(400, "malformed request line"), None);
+    };
+
+    let mut content_length = 0;
+    let (mut origin, mut content_type) = (None, None);
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((key, value)) = header.split_once(':') {
+            let (key, value) = (key.trim(), value.trim());
+            if key.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().unwrap_or(0);
+            } else if key.eq_ignore_ascii_case("origin") {
+                origin = Some(value.to_string());
+            } else if key.eq_ignore_ascii_case("content-type") {
+                content_type = Some(value.to_string());
+            }
+        }
+    }
+    // only a browser sends an origin, clients that aren't web pages are let in without one
+    let allowed = origin.as_deref().filter(|origin| origins.iter().any(|allowed| allowed == origin));
+    if content_length > MAX_BODY {
+        return write_response(stream, &Response::error(413, "body too large"), allowed);
+    }
+
+    // read even when it's refused, closing with the body unread resets the connection before the client gets the answer
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    if origin.is_some() && allowed.is_none() && matches!(method, "POST" | "OPTIONS") {
+        return write_response(stream, &Response::error(403, "this origin is not allowed"), None);
+    }
+    // parameters like "; charset=utf-8" are fine
+    let json = content_type
+        .as_deref()
+        .is_some_and(|content_type| content_type.split(';').next().unwrap_or("").trim().eq_ignore_ascii_case("application/json"));
+    if method == "POST" && !json {
+        return write_response(stream, &Response::error(415, "send the body as application/json"), allowed);
+    }
+
+    let response = match (method, String::from_utf8(body)) {
+        // browsers ask before posting json from another origin
+        ("OPTIONS", _) => Response {
+            status: 204,
+            body: String::new(),
+        },
+        // a request that panicked only loses its own answer, the service goes on for the next ones
+        (_, Ok(body)) => service.lock().unwrap_or_else(PoisonError::into_inner).handle(method, path, &body),
+        (_, Err(_)) => Response::error(400, "body is not utf-8"),
+    };
+    write_response(stream, &response, allowed)
+}
+
+// `origin` is the request's, when it's allowed
+fn write_response(mut stream: TcpStream, response: &Response, origin: Option<&str>) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    };
+    let cors = match origin {
+        Some(origin) => format!(
+            "Access-Control-Allow-Origin: {}\r\nAccess-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+             Access-Control-Allow-Headers: Content-Type\r\nVary: Origin\r\n",
+            origin
+        ),
+        None => String::new(),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.body.len(),
+        cors,
+        response.body
+    )?;
+    stream.flush()
+}
@@ -0,0 +1,214 @@
+// recordings of what was drawn, to reproduce a recognition someone saw ("my star is always a pigtail"):
+// one json object per line, written as it happens so a crash doesn't lose it
+//   {"templates": {"at", "pack"}}  the templates strokes were recognized against from then on (a pack::to_json)
+//   {"stroke": {"at", "backend", "reject_distance", "points", "outcome"}}  a finished stroke and what it was recognized as
+// `at` is in seconds since the app started; replay() runs the strokes through the recognizer again headless,
+// the plugin can also feed them back through its own capture (GestureConfig::replay)
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::Recognizer;
+use crate::pack::{self, PackError, StrokePointFile};
+use crate::recognizer::Backend;
+use crate::stroke::StrokePoint;
+use crate::templates::Template;
+
+// scores of a replayed stroke may differ by this much (relative to the score, absolute below 1) and still match,
+// float math isn't bit for bit the same across platforms and builds
+const SCORE_TOLERANCE: f32 = 1e-3;
+
+// what a stroke was recognized as
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Outcome {
+    pub name: Option<String>, // the closest gesture, also when it was too far away to count
+    pub score: Option<f32>,
+    pub recognized: bool,
+}
+
+impl Outcome {
+    // the closest gesture and its distance, which only counts when it's within reject_distance
+    pub fn new(closest: Option<(&str, f32)>, reject_distance: f32) -> Self {
+        Self {
+            name: closest.map(|(name, _)| name.to_string()),
+            score: closest.map(|(_, score)| score),
+            recognized: closest.is_some_and(|(_, score)| score <= reject_distance),
+        }
+    }
+
+    // the same gesture, just as recognized, and nearly the same score
+    pub fn matches(&self, other: &Outcome) -> bool {
+        let scores_match = match (self.score, other.score) {
+            (Some(a), Some(b)) => (a - b).abs() <= SCORE_TOLERANCE * a.abs().max(b.abs()).max(1.0),
+            (a, b) => a == b,
+        };
+        self.name == other.name && self.recognized == other.recognized && scores_match
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.name, self.score, self.recognized) {
+            (Some(name), Some(score), true) => write!(f, "{} ({:.2})", name, score),
+            (Some(name), Some(score), false) => write!(f, "not recognized (closest {} at {:.2})", name, score),
+            _ => write!(f, "not recognized"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordedStroke {
+    pub at: f32,
+    pub backend: Backend,
+    pub reject_distance: f32,
+    pub stroke: Vec<StrokePoint>, // t relative to the stroke's start, like BoardStrokes keeps them
+    pub outcome: Outcome,
+}
+
+#[derive(Clone, Debug)]
+pub enum SessionEvent {
+    Templates { at: f32, templates: HashMap<String, HashSet<Template>> },
+    Stroke(RecordedStroke),
+}
+
+impl SessionEvent {
+    pub fn at(&self) -> f32 {
+        match self {
+            SessionEvent::Templates { at, .. } => *at,
+            SessionEvent::Stroke(stroke) => stroke.at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum EventFile {
+    Templates {
+        at: f32,
+        pack: String,
+    },
+    Stroke {
+        at: f32,
+        backend: String,
+        reject_distance: f32,
+        points: Vec<StrokePointFile>,
+        outcome: Outcome,
+    },
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    Json { line: usize, error: serde_json::Error },
+    Pack { line: usize, error: PackError },
+    Backend { line: usize, name: String },
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Json { line, error } => write!(f, "line {}: invalid session event: {}", line, error),
+            SessionError::Pack { line, error } => write!(f, "line {}: {}", line, error),
+            SessionError::Backend { line, name } => write!(f, "line {}: unknown backend {}", line, name),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+// appends events to a recording, a line at a time
+pub struct SessionWriter<W: Write>(W);
+
+impl<W: Write> SessionWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self(writer)
+    }
+
+    pub fn write(&mut self, event: &SessionEvent) -> io::Result<()> {
+        let file = match event {
+            SessionEvent::Templates { at, templates } => EventFile::Templates {
+                at: *at,
+                pack: pack::to_json(templates),
+            },
+            SessionEvent::Stroke(stroke) => EventFile::Stroke {
+                at: stroke.at,
+                backend: stroke.backend.name().to_string(),
+                reject_distance: stroke.reject_distance,
+                points: stroke.stroke.iter().map(StrokePointFile::from).collect(),
+                outcome: stroke.outcome.clone(),
+            },
+        };
+        serde_json::to_writer(&mut self.0, &file)?;
+        self.0.write_all(b"\n")?;
+        self.0.flush()
+    }
+}
+
+pub fn read_session(text: &str) -> Result<Vec<SessionEvent>, SessionError> {
+    let mut events = Vec::new();
+    for (index, json) in text.lines().enumerate().filter(|(_, json)| !json.trim().is_empty()) {
+        let line = index + 1;
+        let file: EventFile = serde_json::from_str(json).map_err(|error| SessionError::Json { line, error })?;
+        events.push(match file {
+            EventFile::Templates { at, pack } => SessionEvent::Templates {
+                at,
+                templates: pack::from_json(&pack).map_err(|error| SessionError::Pack { line, error })?,
+            },
+            EventFile::Stroke {
+                at,
+                backend,
+                reject_distance,
+                points,
+                outcome,
+            } => SessionEvent::Stroke(RecordedStroke {
+                at,
+                backend: Backend::from_name(&backend).ok_or(SessionError::Backend { line, name: backend })?,
+                reject_distance,
+                stroke: points.iter().map(StrokePoint::from).collect(),
+                outcome,
+            }),
+        });
+    }
+    Ok(events)
+}
+
+pub struct Replayed {
+    pub at: f32,
+    pub recorded: Outcome,
+    pub replayed: Outcome,
+}
+
+impl Replayed {
+    pub fn matches(&self) -> bool {
+        self.recorded.matches(&self.replayed)
+    }
+}
+
+// every recorded stroke recognized again, against the templates and with the settings it was recorded with
+// (strokes recorded before any templates were get the built-in gestures)
+pub fn replay(events: &[SessionEvent]) -> Vec<Replayed> {
+    let mut recognizer = Recognizer::default();
+    let mut replayed = Vec::new();
+    for event in events.iter() {
+        match event {
+            SessionEvent::Templates { templates, .. } => recognizer = Recognizer::new(templates.clone(), recognizer.backend()),
+            SessionEvent::Stroke(stroke) => {
+                if recognizer.backend() != stroke.backend {
+                    recognizer.set_backend(stroke.backend);
+                }
+                let recognition = recognizer.recognize(&stroke.stroke);
+                replayed.push(Replayed {
+                    at: stroke.at,
+                    recorded: stroke.outcome.clone(),
+                    replayed: Outcome::new(
+                        recognition.as_ref().map(|recognition| (recognition.name.as_str(), recognition.score)),
+                        stroke.reject_distance,
+                    ),
+                });
+            }
+        }
+    }
+    replayed
+}
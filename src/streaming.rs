@@ -0,0 +1,98 @@
+// recognition while the stroke is still being drawn: every template is cut into prefixes (the first quarter,
+// the first three eighths, ...) and the partial stroke is matched against those, so the best prefix says both
+// which gesture it's turning into and how far along it is
+
+use std::collections::{HashMap, HashSet};
+
+use glam::Vec2;
+
+use crate::recognizer::{normalize_with_transform, path_length, search_best_angle, RESAMPLE_TARGET_POINTS};
+use crate::templates::Template;
+
+// prefixes are cut at every eighth of a template
+pub const PREFIX_STEPS: usize = 8;
+// anything shorter than a quarter of a template is too little to tell gestures apart
+const MIN_PREFIX_STEPS: usize = 2;
+
+struct PrefixTemplate {
+    name: String,
+    progress: f32, // how much of the template the prefix is, 0 to 1
+    points: [Vec2; RESAMPLE_TARGET_POINTS],
+    // the whole template, normalized the way the prefix was, so it can be put over the partial stroke
+    completion: Vec<Vec2>,
+}
+
+// the prefixes of every template, built once whenever the templates change
+pub struct PrefixIndex(Vec<PrefixTemplate>);
+
+#[derive(Clone, Debug)]
+pub struct Guess {
+    pub name: String,
+    // 0 when the runner-up gesture matches just as well, towards 1 the further ahead the best one is
+    pub confidence: f32,
+    pub progress: f32,
+    pub distance: f32,
+    // the rest of the gesture as it would continue from the partial stroke, in the stroke's coordinates
+    pub completion: Vec<Vec2>,
+}
+
+impl PrefixIndex {
+    pub fn new(templates: &HashMap<String, HashSet<Template>>) -> Self {
+        let mut prefixes = Vec::new();
+        for (name, set) in templates.iter() {
+            for template in set.iter() {
+                for step in MIN_PREFIX_STEPS..=PREFIX_STEPS {
+                    let end = (RESAMPLE_TARGET_POINTS * step).div_ceil(PREFIX_STEPS);
+                    let prefix = &template.points[..end];
+                    let (points, normalization) = normalize_with_transform(path_length(prefix), prefix);
+                    // resampling can come up a point short, those prefixes are skipped like too short strokes are
+                    let Ok(points) = points.try_into() else {
+                        continue;
+                    };
+                    prefixes.push(PrefixTemplate {
+                        name: name.clone(),
+                        progress: step as f32 / PREFIX_STEPS as f32,
+                        points,
+                        completion: normalization.apply(&template.points),
+                    });
+                }
+            }
+        }
+        Self(prefixes)
+    }
+
+    // the raw points of the stroke so far
+    pub fn guess(&self, stroke: &[Vec2]) -> Option<Guess> {
+        if stroke.len() < 2 {
+            return None;
+        }
+        let (points, normalization) = normalize_with_transform(path_length(stroke), stroke);
+
+        // the closest prefix of every gesture
+        let mut nearest: HashMap<&str, (f32, f32, &PrefixTemplate)> = HashMap::new();
+        for prefix in self.0.iter() {
+            let (distance, angle) = search_best_angle(&points, &prefix.points);
+            let entry = nearest.entry(prefix.name.as_str()).or_insert((f32::MAX, 0.0, prefix));
+            if distance < entry.0 {
+                *entry = (distance, angle, prefix);
+            }
+        }
+
+        let mut ranked: Vec<(f32, f32, &PrefixTemplate)> = nearest.into_values().collect();
+        ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let &(distance, angle, prefix) = ranked.first()?;
+        let confidence = match ranked.get(1) {
+            Some((runner_up, _, _)) if *runner_up > 0.0 => 1.0 - distance / runner_up,
+            Some(_) => 0.0,
+            None => 1.0,
+        };
+
+        Some(Guess {
+            name: prefix.name.clone(),
+            confidence,
+            progress: prefix.progress,
+            distance,
+            completion: normalization.denormalize(&prefix.completion, angle),
+        })
+    }
+}
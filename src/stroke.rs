@@ -0,0 +1,62 @@
+// a captured point with everything the input device told us about it, not just where it was
+// the matching itself only ever looks at positions, the rest rides along for whatever wants it
+// (velocity features, variable width rendering, templates that remember how they were drawn)
+
+use glam::Vec2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokePoint {
+    pub pos: Vec2,
+    pub t: f32,            // seconds since the stroke began
+    pub pressure: f32,     // 0 to 1, devices without pressure (mice) report 1
+    pub tilt: Option<f32>, // altitude angle of the pen in rads, 0 lying flat and pi/2 straight up
+}
+
+impl StrokePoint {
+    pub fn new(pos: Vec2, t: f32) -> Self {
+        Self {
+            pos,
+            t,
+            pressure: 1.0,
+            tilt: None,
+        }
+    }
+}
+
+// anything resample() can walk along and interpolate between
+pub trait Sample: Copy {
+    fn pos(&self) -> Vec2;
+    fn lerp(&self, other: &Self, alpha: f32) -> Self;
+}
+
+impl Sample for Vec2 {
+    fn pos(&self) -> Vec2 {
+        *self
+    }
+
+    fn lerp(&self, other: &Self, alpha: f32) -> Self {
+        Vec2::lerp(*self, *other, alpha)
+    }
+}
+
+impl Sample for StrokePoint {
+    fn pos(&self) -> Vec2 {
+        self.pos
+    }
+
+    fn lerp(&self, other: &Self, alpha: f32) -> Self {
+        Self {
+            pos: self.pos.lerp(other.pos, alpha),
+            t: self.t + (other.t - self.t) * alpha,
+            pressure: self.pressure + (other.pressure - self.pressure) * alpha,
+            tilt: match (self.tilt, other.tilt) {
+                (Some(a), Some(b)) => Some(a + (b - a) * alpha),
+                (tilt, None) | (None, tilt) => tilt,
+            },
+        }
+    }
+}
+
+pub fn positions(stroke: &[StrokePoint]) -> Vec<Vec2> {
+    stroke.iter().map(|point| point.pos).collect()
+}
@@ -0,0 +1,181 @@
+// synthetic gestures: perturbed copies of a stroke so one drawing can stand in for many people drawing it
+// (the "gesture synthesis" trick from the $-family papers)
+// the perturbations are applied to the raw stroke, so the results still have to go through the pipeline;
+// that makes them usable both as extra templates and as fake user input for evaluating the recognizer
+
+use std::collections::{HashMap, HashSet};
+
+use glam::Vec2;
+use rand::Rng;
+
+use crate::recognizer::{get_centroid, normalize, path_length, to_template, RESAMPLE_TARGET_POINTS};
+use crate::stroke::{positions, StrokePoint};
+use crate::templates::Template;
+
+// every field is the maximum amount of that perturbation, each variant picks a random amount up to it
+pub struct SyntheticConfig {
+    pub scale: f32,    // relative change of the width/height (0.2 = 80% to 120%), x and y picked separately
+    pub skew: f32,     // shear factor in both directions
+    pub rotation: f32, // in rads
+    pub noise: f32,    // offset of every point, relative to the size of the stroke
+    pub speed: f32,    // how much the pen "speed" (spacing between samples) may drift from its average
+}
+
+impl Default for SyntheticConfig {
+    fn default() -> Self {
+        Self {
+            scale: 0.2,
+            skew: 0.15,
+            rotation: 0.174_532_93, // 10 deg in rads
+            noise: 0.02,
+            speed: 0.4,
+        }
+    }
+}
+
+pub fn synthesize<R: Rng>(points: &[Vec2], config: &SyntheticConfig, rng: &mut R) -> Vec<Vec2> {
+    let mut synthetic_points = resample_with_varying_speed(points, config.speed, rng);
+    if synthetic_points.len() < 2 {
+        return synthetic_points;
+    }
+
+    // GET BOUNDING BOX SIZE FOR THE NOISE
+    let (mut min, mut max) = (Vec2::MAX, Vec2::MIN);
+    for point in synthetic_points.iter() {
+        min = min.min(*point);
+        max = max.max(*point);
+    }
+    let size = (max - min).max_element();
+
+    let scale_x = 1.0 + rng.gen_range(-config.scale..=config.scale);
+    let scale_y = 1.0 + rng.gen_range(-config.scale..=config.scale);
+    let skew_x = rng.gen_range(-config.skew..=config.skew);
+    let skew_y = rng.gen_range(-config.skew..=config.skew);
+    let theta = rng.gen_range(-config.rotation..=config.rotation);
+    let cos = theta.cos();
+    let sin = theta.sin();
+
+    // everything happens about the centroid so the stroke stays where it was drawn
+    let centroid = get_centroid(&synthetic_points);
+    for point in synthetic_points.iter_mut() {
+        let noise = Vec2::new(
+            rng.gen_range(-config.noise..=config.noise),
+            rng.gen_range(-config.noise..=config.noise),
+        ) * size;
+        let p = *point + noise - centroid;
+
+        let skewed = Vec2::new(p.x + skew_x * p.y, p.y + skew_y * p.x);
+        let scaled = Vec2::new(skewed.x * scale_x, skewed.y * scale_y);
+        *point = Vec2::new(
+            scaled.x * cos + scaled.y * sin,
+            scaled.y * cos - scaled.x * sin,
+        ) + centroid;
+    }
+
+    synthetic_points
+}
+
+// re-samples the stroke with a random amount of points whose spacing follows a random walk,
+// like the stroke was drawn speeding up and slowing down; corners get cut differently every time
+fn resample_with_varying_speed<R: Rng>(points: &[Vec2], speed: f32, rng: &mut R) -> Vec<Vec2> {
+    let total_length = path_length(points);
+    if points.len() < 2 || total_length <= 0.0 {
+        return points.to_vec();
+    }
+
+    let samples = rng.gen_range(RESAMPLE_TARGET_POINTS / 2..=RESAMPLE_TARGET_POINTS * 2);
+    let mut velocity = 1.0;
+    let mut gaps = Vec::with_capacity(samples);
+    for _ in 1..samples {
+        velocity = (velocity + rng.gen_range(-speed..=speed) * 0.5).clamp(1.0 - speed.min(0.9), 1.0 + speed);
+        gaps.push(velocity);
+    }
+    let gap_scale = total_length / gaps.iter().sum::<f32>();
+
+    let mut resampled_points = Vec::with_capacity(samples);
+    resampled_points.push(points[0]);
+    let mut target = 0.0;
+    let mut walked = 0.0;
+    let mut segment = 1;
+    for gap in gaps {
+        target += gap * gap_scale;
+        while segment < points.len() - 1 && walked + points[segment - 1].distance(points[segment]) < target {
+            walked += points[segment - 1].distance(points[segment]);
+            segment += 1;
+        }
+
+        let segment_length = points[segment - 1].distance(points[segment]);
+        let alpha = if segment_length > 0.0 { ((target - walked) / segment_length).min(1.0) } else { 0.0 };
+        resampled_points.push(points[segment - 1].lerp(points[segment], alpha));
+    }
+
+    resampled_points
+}
+
+pub fn variants<R: Rng>(points: &[Vec2], count: usize, config: &SyntheticConfig, rng: &mut R) -> Vec<Vec<Vec2>> {
+    (0..count).map(|_| synthesize(points, config, rng)).collect()
+}
+
+// adds `count` synthetic templates next to every existing template of every gesture;
+// a template that remembers its stroke gets copies of the stroke perturbed, drawn in the same time
+pub fn enrich_templates<R: Rng>(
+    templates: &mut HashMap<String, HashSet<Template>>,
+    count: usize,
+    config: &SyntheticConfig,
+    rng: &mut R,
+) {
+    let mut names: Vec<String> = templates.keys().cloned().collect();
+    names.sort(); // so a seeded rng hands every template the same variants each run
+    for name in names {
+        let set = templates.get_mut(&name).expect("the name was just taken from the map");
+        let originals: Vec<(Vec<Vec2>, Option<f32>)> = crate::templates::sorted(set)
+            .iter()
+            .map(|template| match template.stroke.as_slice() {
+                [first, .., last] => (positions(&template.stroke), Some(last.t - first.t)),
+                _ => (template.points.to_vec(), None),
+            })
+            .collect();
+        for (points, duration) in originals {
+            for variant in variants(&points, count, config, rng) {
+                let Some(mut template) = to_template(&normalize(&variant)) else {
+                    continue;
+                };
+                // the varying spacing of the samples stands in for the varying speed
+                if let Some(duration) = duration {
+                    let last = (variant.len() - 1).max(1) as f32;
+                    template.stroke = variant
+                        .iter()
+                        .enumerate()
+                        .map(|(i, point)| StrokePoint::new(*point, duration * i as f32 / last))
+                        .collect();
+                }
+                set.insert(template);
+            }
+        }
+    }
+}
+
+// labelled raw strokes for testing the recognizer with, `count` per template (from its stroke, when it remembers one)
+pub fn dataset<R: Rng>(
+    templates: &HashMap<String, HashSet<Template>>,
+    count: usize,
+    config: &SyntheticConfig,
+    rng: &mut R,
+) -> Vec<(String, Vec<Vec2>)> {
+    let mut names: Vec<&String> = templates.keys().collect();
+    names.sort(); // hashmap order is random, keep the classes (and their templates) in a stable order
+
+    let mut samples = Vec::new();
+    for name in names {
+        for template in crate::templates::sorted(&templates[name]) {
+            let points = match template.stroke.as_slice() {
+                [_, _, ..] => positions(&template.stroke),
+                _ => template.points.to_vec(),
+            };
+            for variant in variants(&points, count, config, rng) {
+                samples.push((name.clone(), variant));
+            }
+        }
+    }
+    samples
+}
@@ -3,14 +3,44 @@
 // since the shapes are resampled to 64 pixels so are these templates
 
 use std::{collections::{HashMap, HashSet}, hash::Hash};
-use bevy::math::Vec2;
+use glam::Vec2;
+
+use crate::stroke::StrokePoint;
+use crate::temporal::{SpeedRange, TemporalFeatures};
 
 const SIZE: usize = 64;
-pub struct Template(pub [Vec2; SIZE]);
+#[derive(Clone, Debug)]
+pub struct Template {
+    pub points: [Vec2; SIZE],
+    // the raw stroke the template was made from, with timing and pressure
+    // (the built-in ones only have their points turned back the way they're drawn, untimed)
+    pub stroke: Vec<StrokePoint>,
+    // only match strokes drawn this fast, for gestures that share a shape (a slow swipe and a flick)
+    pub speed: Option<SpeedRange>,
+}
+
+impl Template {
+    pub fn new(points: [Vec2; SIZE]) -> Self {
+        Self::with_stroke(points, Vec::new())
+    }
+
+    pub fn with_stroke(points: [Vec2; SIZE], stroke: Vec<StrokePoint>) -> Self {
+        Self { points, stroke, speed: None }
+    }
+
+    // whether a stroke drawn like this may match the template, without timing there's nothing to check
+    pub fn allows(&self, temporal: Option<&TemporalFeatures>) -> bool {
+        match (self.speed, temporal) {
+            (Some(speed), Some(temporal)) => speed.contains(temporal),
+            _ => true,
+        }
+    }
+}
 
+// two templates are the same template when their points are, however they were drawn
 impl PartialEq for Template {
     fn eq(&self, other: &Self) -> bool {
-        self.0.iter().zip(other.0.iter()).all(|(x,y)| x == y)
+        self.points.iter().zip(other.points.iter()).all(|(x,y)| x == y)
     }
 }
 
@@ -18,16 +48,61 @@ impl Eq for Template {}
 
 impl Hash for Template {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        for element in &self.0 {
+        for element in &self.points {
             element.x.to_bits().hash(state);
             element.y.to_bits().hash(state);
         }
     }
 }
 
+// hashset order changes from run to run, anything that has to come out the same every time walks them in this order
+pub fn sorted(set: &HashSet<Template>) -> Vec<&Template> {
+    let mut templates: Vec<&Template> = set.iter().collect();
+    templates.sort_by_cached_key(|template| template.points.map(|point| (point.x.to_bits(), point.y.to_bits())));
+    templates
+}
+
+// how the built-in gestures look when they're drawn (default_templates.png, the figure of the $1 paper): the angle from
+// the centroid to the first point in degrees (y pointing down) and the width over the height. the pipeline turned that
+// angle to 0 and squashed them into a square
+const DRAWN: [(&str, f32, f32); 16] = [
+    ("Triangle", -97.0, 1.5),
+    ("X", -135.0, 0.9),
+    ("Rectangle", -148.0, 1.6),
+    ("Circle", -60.0, 1.0),
+    ("Check", 170.0, 1.0),
+    ("Caret", 125.0, 1.0),
+    ("Zig-Zag", 162.0, 3.3),
+    ("Arrow", 175.0, 2.0),
+    ("Left Square Bracket", -48.0, 0.5),
+    ("Right Square Bracket", -128.0, 0.6),
+    ("V", -144.0, 1.0),
+    ("Delete", -105.0, 0.85),
+    ("Left Curly Bracket", -60.0, 0.35),
+    ("Right Curly Bracket", -124.0, 0.35),
+    ("Star", 120.0, 1.2),
+    ("Pigtail", 160.0, 1.7),
+];
+
+// a built-in template with the stroke it was drawn with, as near as the normalized points tell:
+// turned back upright and stretched to the proportions of the figure, for rubine.rs to take features from
+fn drawn(template: Template, angle: f32, aspect: f32) -> Template {
+    let rotation = Vec2::from_angle(angle.to_radians());
+    let upright: Vec<Vec2> = template.points.iter().map(|point| rotation.rotate(*point)).collect();
+    let (mut min, mut max) = (Vec2::MAX, Vec2::MIN);
+    for point in upright.iter() {
+        min = min.min(*point);
+        max = max.max(*point);
+    }
+    let size = max - min;
+    let stretch = Vec2::new(aspect * size.y / size.x, 1.0);
+    let stroke = upright.iter().map(|point| StrokePoint::new(*point * stretch, 0.0)).collect();
+    Template::with_stroke(template.points, stroke)
+}
+
 pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
     let mut h = HashMap::new();
-    h.insert(String::from("Triangle"), HashSet::from([Template([
+    h.insert(String::from("Triangle"), HashSet::from([Template::new([
         Vec2::new(66.62665, -4.5776367e-5),
         Vec2::new(61.4805, -1.5734406),
         Vec2::new(56.128845, -3.1351013),
@@ -93,7 +168,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(59.699036, 5.968521),
         Vec2::new(64.58249, 3.5591125),
     ])]));
-    h.insert(String::from("Circle"), HashSet::from([Template([
+    h.insert(String::from("Circle"), HashSet::from([Template::new([
         Vec2::new(44.340607, -7.6293945e-5),
         Vec2::new(46.803467, -3.9438019),
         Vec2::new(46.515076, -8.88736),
@@ -159,7 +234,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(47.989746, -11.305115),
         Vec2::new(47.372894, -16.190582),
     ])]));
-    h.insert(String::from("X"), HashSet::from([Template([
+    h.insert(String::from("X"), HashSet::from([Template::new([
         Vec2::new(55.426727, 7.6293945e-6),
         Vec2::new(50.719696, 0.09410858),
         Vec2::new(45.935944, -0.33444977),
@@ -225,7 +300,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(25.344116, -48.91314),
         Vec2::new(26.374481, -52.66333),
     ])]));
-    h.insert(String::from("Rectangle"), HashSet::from([Template([
+    h.insert(String::from("Rectangle"), HashSet::from([Template::new([
         Vec2::new(49.165726, -3.8146973e-5),
         Vec2::new(46.848648, -4.061165),
         Vec2::new(44.531586, -8.122299),
@@ -291,7 +366,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(43.75821, 8.543686),
         Vec2::new(47.44197, 5.9892426),
     ])]));
-    h.insert(String::from("Check"), HashSet::from([Template([
+    h.insert(String::from("Check"), HashSet::from([Template::new([
         Vec2::new(45.207275, 7.6293945e-5),
         Vec2::new(43.531677, -2.3918152),
         Vec2::new(41.9494, -4.9904785),
@@ -357,7 +432,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(-52.97744, 50.83139),
         Vec2::new(-54.792717, 52.777847),
     ])]));
-    h.insert(String::from("Caret"), HashSet::from([Template([
+    h.insert(String::from("Caret"), HashSet::from([Template::new([
         Vec2::new(68.49693, 0.0),
         Vec2::new(66.263535, 2.4131927),
         Vec2::new(63.605453, 4.4089203),
@@ -423,7 +498,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(-17.218582, -65.70145),
         Vec2::new(-17.363693, -68.93118),
     ])]));
-    h.insert(String::from("Zig-Zag"), HashSet::from([Template([
+    h.insert(String::from("Zig-Zag"), HashSet::from([Template::new([
         Vec2::new(51.137466, 0.0),
         Vec2::new(49.388718, 4.978821),
         Vec2::new(47.632797, 9.84082),
@@ -489,7 +564,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(-47.09629, 7.347168),
         Vec2::new(-48.86254, 12.169312),
     ])]));
-    h.insert(String::from("Arrow"), HashSet::from([Template([
+    h.insert(String::from("Arrow"), HashSet::from([Template::new([
         Vec2::new(70.09819, -9.1552734e-5),
         Vec2::new(66.869644, -0.69758606),
         Vec2::new(63.579514, -0.5935669),
@@ -555,7 +630,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(-19.819504, -54.293953),
         Vec2::new(-18.5999, -59.851524),
     ])]));
-    h.insert(String::from("Left Square Bracket"), HashSet::from([Template([
+    h.insert(String::from("Left Square Bracket"), HashSet::from([Template::new([
         Vec2::new(58.312958, -1.5258789e-5),
         Vec2::new(56.89264, -2.7177582),
         Vec2::new(54.830017, -5.089432),
@@ -621,7 +696,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(-7.9244385, 57.723694),
         Vec2::new(-6.0291595, 60.19983),    
     ])]));
-    h.insert(String::from("Right Square Bracket"), HashSet::from([Template([
+    h.insert(String::from("Right Square Bracket"), HashSet::from([Template::new([
         Vec2::new(58.192993, 0.0),
         Vec2::new(55.961792, 2.0500946),
         Vec2::new(53.63965, 3.9964447),
@@ -687,7 +762,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(-14.596588, -57.94673),
         Vec2::new(-12.635315, -60.357307),
     ])]));
-    h.insert(String::from("V"), HashSet::from([Template([
+    h.insert(String::from("V"), HashSet::from([Template::new([
         Vec2::new(71.28351, -3.0517578e-5),
         Vec2::new(68.06894, -1.0181732),
         Vec2::new(64.60895, -1.4554749),
@@ -753,7 +828,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(-15.481873, 61.43495),
         Vec2::new(-14.279724, 64.41675),
     ])]));
-    h.insert(String::from("Delete"), HashSet::from([Template([
+    h.insert(String::from("Delete"), HashSet::from([Template::new([
         Vec2::new(58.043304, 0.0),
         Vec2::new(53.659912, 1.0546875),
         Vec2::new(49.262787, 2.028717),
@@ -819,7 +894,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(39.644653, 44.877487),
         Vec2::new(42.017944, 49.167984),
     ])]));
-    h.insert(String::from("Left Curly Bracket"), HashSet::from([Template([
+    h.insert(String::from("Left Curly Bracket"), HashSet::from([Template::new([
         Vec2::new(53.067596, 1.5258789e-5),
         Vec2::new(52.66304, -4.8795013),
         Vec2::new(52.258514, -9.759048),
@@ -885,7 +960,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(-44.259933, 61.599976),
         Vec2::new(-43.855392, 66.47949),
     ])]));
-    h.insert(String::from("Right Curly Bracket"), HashSet::from([Template([
+    h.insert(String::from("Right Curly Bracket"), HashSet::from([Template::new([
         Vec2::new(56.633347, 0.0),
         Vec2::new(55.603653, 3.972107),
         Vec2::new(54.57396, 7.9441986),
@@ -951,7 +1026,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(-39.347244, -62.543694),
         Vec2::new(-39.60327, -66.06149),
     ])]));
-    h.insert(String::from("Star"), HashSet::from([Template([
+    h.insert(String::from("Star"), HashSet::from([Template::new([
         Vec2::new(55.735703, 0.0),
         Vec2::new(48.909393, 3.4066467),
         Vec2::new(41.459656, 5.1355133),
@@ -1017,7 +1092,7 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(43.09526, -4.7387695),
         Vec2::new(50.43518, -3.9717102),
     ])]));
-    h.insert(String::from("Pigtail"), HashSet::from([Template([
+    h.insert(String::from("Pigtail"), HashSet::from([Template::new([
         Vec2::new(66.00748, -7.6293945e-6),
         Vec2::new(63.467392, -3.2673187),
         Vec2::new(59.7836, -5.73201),
@@ -1083,5 +1158,11 @@ pub fn stroke_templates() -> HashMap<String, HashSet<Template>> {
         Vec2::new(-29.515167, -51.149437),
         Vec2::new(-33.389923, -53.337708),
     ])]));
+
+    for (name, angle, aspect) in DRAWN {
+        if let Some(set) = h.get_mut(name) {
+            *set = set.drain().map(|template| drawn(template, angle, aspect)).collect();
+        }
+    }
     h
 }
\ No newline at end of file
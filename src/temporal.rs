@@ -0,0 +1,100 @@
+// how a stroke was drawn rather than what it looks like: the shape matching can't tell a slow swipe from a flick,
+// these can. only strokes captured with timestamps have them (the built-in templates don't)
+
+use crate::stroke::StrokePoint;
+
+// slower than this (in px/s) between two samples counts as the pen resting
+const PAUSE_VELOCITY: f32 = 30.0;
+// and it has to rest at least this long (in seconds) for it to be a pause
+const PAUSE_DURATION: f32 = 0.15;
+// velocities are measured over at least this long, a single 1ms sample would make the peak meaningless
+const VELOCITY_WINDOW: f32 = 0.02;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TemporalFeatures {
+    pub duration: f32,         // seconds
+    pub average_velocity: f32, // px/s over the whole stroke
+    pub peak_velocity: f32,    // px/s
+    pub pauses: usize,
+    pub pause_time: f32, // seconds spent in pauses
+}
+
+// the average velocity (px/s) a template accepts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpeedRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl SpeedRange {
+    pub fn contains(&self, features: &TemporalFeatures) -> bool {
+        (self.min..=self.max).contains(&features.average_velocity)
+    }
+}
+
+pub fn temporal_features(stroke: &[StrokePoint]) -> Option<TemporalFeatures> {
+    let first = stroke.first()?;
+    let last = stroke.last()?;
+    let duration = last.t - first.t;
+    if duration <= 0.0 {
+        return None; // no timing information
+    }
+
+    let length: f32 = stroke.windows(2).map(|pair| pair[0].pos.distance(pair[1].pos)).sum();
+
+    let mut peak_velocity: f32 = 0.0;
+    let mut window_start = 0;
+    let mut pauses = 0;
+    let mut pause_time = 0.0;
+    let mut resting_since: Option<f32> = None;
+
+    for (i, pair) in stroke.windows(2).enumerate() {
+        let dt = pair[1].t - pair[0].t;
+        let distance = pair[0].pos.distance(pair[1].pos);
+
+        // the cursor only reports when it moves, so resting shows up either as slow samples or one long gap
+        let resting = dt > 0.0 && distance / dt < PAUSE_VELOCITY;
+        match (resting, resting_since) {
+            (true, None) => resting_since = Some(pair[0].t),
+            (false, Some(since)) => {
+                let rested = pair[0].t - since;
+                if rested >= PAUSE_DURATION {
+                    pauses += 1;
+                    pause_time += rested;
+                }
+                resting_since = None;
+            }
+            _ => {}
+        }
+
+        // grow the window until it's long enough to measure a velocity over
+        while window_start < i && pair[1].t - stroke[window_start + 1].t >= VELOCITY_WINDOW {
+            window_start += 1;
+        }
+        let window_time = pair[1].t - stroke[window_start].t;
+        if window_time >= VELOCITY_WINDOW {
+            let window_length: f32 = stroke[window_start..=i + 1]
+                .windows(2)
+                .map(|pair| pair[0].pos.distance(pair[1].pos))
+                .sum();
+            peak_velocity = peak_velocity.max(window_length / window_time);
+        }
+    }
+    if let Some(since) = resting_since {
+        let rested = last.t - since;
+        if rested >= PAUSE_DURATION {
+            pauses += 1;
+            pause_time += rested;
+        }
+    }
+
+    let average_velocity = length / duration;
+    Some(TemporalFeatures {
+        duration,
+        average_velocity,
+        // a stroke shorter than the window never gets a peak measured, its average is the best there is
+        peak_velocity: peak_velocity.max(average_velocity),
+        pauses,
+        pause_time,
+    })
+}
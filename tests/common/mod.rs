@@ -0,0 +1,28 @@
+// shared by the test files: the generated strokes and a recognizer per backend over the built-in gestures
+#![allow(dead_code)] // not every test file uses every helper
+
+use dollar1_unistroke::engine::Recognizer;
+use dollar1_unistroke::formats::parse_strokes;
+use dollar1_unistroke::recognizer::Backend;
+use dollar1_unistroke::stroke::StrokePoint;
+
+// three raw strokes per built-in gesture, in window coordinates with timestamps; generated (not recorded in the app)
+// by examples/generated_strokes.rs from gestures described there by hand rather than from the templates
+pub fn generated_strokes() -> Vec<(String, Vec<StrokePoint>)> {
+    parse_strokes(include_str!("../data/generated_strokes.json"))
+        .expect("the generated strokes parse")
+        .into_iter()
+        .map(|file| (file.name.expect("every generated stroke is labelled"), file.stroke))
+        .collect()
+}
+
+pub fn recognizers() -> Vec<Recognizer> {
+    Backend::ALL
+        .into_iter()
+        .map(|backend| {
+            let mut recognizer = Recognizer::default();
+            recognizer.set_backend(backend);
+            recognizer
+        })
+        .collect()
+}
@@ -0,0 +1,57 @@
+// condensing shrinks the template sets without losing a gesture
+
+use std::collections::{HashMap, HashSet};
+
+use dollar1_unistroke::condense::{condense, CondenseOptions};
+use dollar1_unistroke::synthetic::{self, SyntheticConfig};
+use dollar1_unistroke::templates::{stroke_templates, Template};
+use rand::{rngs::StdRng, SeedableRng};
+
+#[test]
+fn condensing_keeps_every_gesture() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut templates = stroke_templates();
+    synthetic::enrich_templates(&mut templates, 4, &SyntheticConfig::default(), &mut rng);
+    let evaluation = synthetic::dataset(&stroke_templates(), 2, &SyntheticConfig::default(), &mut rng);
+    let before: usize = templates.values().map(|set| set.len()).sum();
+
+    let (condensed, report) = condense(&templates, &CondenseOptions::default(), &evaluation);
+
+    let mut names: Vec<&String> = templates.keys().collect();
+    let mut kept: Vec<&String> = condensed.keys().collect();
+    names.sort();
+    kept.sort();
+    assert_eq!(kept, names);
+    for (name, set) in condensed.iter() {
+        assert!(!set.is_empty(), "{} lost all its templates", name);
+    }
+
+    let after: usize = condensed.values().map(|set| set.len()).sum();
+    assert_eq!(report.templates_before, before);
+    assert_eq!(report.templates_after, after);
+    assert_eq!(report.merged + report.removed, before - after);
+    assert!(after < before, "nothing was condensed");
+    // the synthetic strokes are still told apart about as well as with every template
+    let (Some(before), Some(after)) = (report.evaluation_accuracy_before, report.evaluation_accuracy_after) else {
+        panic!("there was an evaluation set");
+    };
+    assert!(after >= before - 0.05, "accuracy went from {} to {}", before, after);
+}
+
+// the sets are rebuilt, so they hash in another order every time, condensing has to come out the same anyway
+#[test]
+fn condensing_is_repeatable() {
+    let mut rng = StdRng::seed_from_u64(27);
+    let mut templates = stroke_templates();
+    synthetic::enrich_templates(&mut templates, 4, &SyntheticConfig::default(), &mut rng);
+
+    let condensed = || {
+        let rebuilt: HashMap<String, HashSet<Template>> =
+            templates.iter().map(|(name, set)| (name.clone(), set.iter().cloned().collect())).collect();
+        condense(&rebuilt, &CondenseOptions::default(), &[]).0
+    };
+    let first = condensed();
+    for _ in 0..5 {
+        assert_eq!(condensed(), first);
+    }
+}
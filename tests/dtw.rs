@@ -0,0 +1,32 @@
+// the dtw matcher: a stroke is no distance from itself, and closer to itself than to anything else
+
+use dollar1_unistroke::recognizer::{dtw_at_angle, dtw_at_best_angle, dtw_distance, DTW_BAND};
+use dollar1_unistroke::templates::stroke_templates;
+
+#[test]
+fn identical_strokes_have_no_distance() {
+    for set in stroke_templates().values() {
+        for template in set.iter() {
+            assert_eq!(dtw_distance(&template.points, &template.points, DTW_BAND), 0.0);
+            // the angle search stops within 2 degrees of the best one
+            let (distance, _) = dtw_at_best_angle(&template.points, &template.points);
+            let resolution = dtw_at_angle(&template.points, &template.points, 2f32.to_radians());
+            assert!(distance <= resolution, "{} at the best angle, {} 2 degrees off", distance, resolution);
+        }
+    }
+}
+
+#[test]
+fn strokes_are_closest_to_themselves() {
+    let templates = stroke_templates();
+    for (name, set) in templates.iter() {
+        let template = set.iter().next().unwrap();
+        let own = dtw_at_best_angle(&template.points, &template.points).0;
+        for (other_name, other_set) in templates.iter().filter(|(other_name, _)| *other_name != name) {
+            for other in other_set.iter() {
+                let distance = dtw_at_best_angle(&template.points, &other.points).0;
+                assert!(own < distance, "{} is as close to {} as to itself", name, other_name);
+            }
+        }
+    }
+}
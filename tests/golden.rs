@@ -0,0 +1,31 @@
+// every built-in gesture has to come out as itself, whichever backend recognizes it
+
+mod common;
+
+use dollar1_unistroke::templates::stroke_templates;
+
+#[test]
+fn templates_are_recognized_as_themselves() {
+    for recognizer in common::recognizers() {
+        for (name, set) in stroke_templates() {
+            for template in set.iter() {
+                // drawn the way the template was (rubine looks at which way it faces, the normalized points are turned)
+                let recognition = recognizer.recognize(&template.stroke).expect("there are templates to match");
+                assert_eq!(recognition.name, name, "{:?}", recognizer.backend());
+                assert!(recognition.recognized, "{} scored {} with {:?}", name, recognition.score, recognizer.backend());
+            }
+        }
+    }
+}
+
+#[test]
+fn generated_strokes_are_recognized_as_their_gesture() {
+    let strokes = common::generated_strokes();
+    for recognizer in common::recognizers() {
+        for (name, stroke) in strokes.iter() {
+            let recognition = recognizer.recognize(stroke).expect("there are templates to match");
+            assert_eq!(&recognition.name, name, "{:?}", recognizer.backend());
+            assert!(recognition.recognized, "{} scored {} with {:?}", name, recognition.score, recognizer.backend());
+        }
+    }
+}
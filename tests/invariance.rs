@@ -0,0 +1,99 @@
+// where a gesture is drawn, how big, and (within reason) at what angle must not change what it's recognized as
+
+mod common;
+
+use std::f32::consts::PI;
+
+use dollar1_unistroke::recognizer::Backend;
+use dollar1_unistroke::stroke::StrokePoint;
+use glam::{Mat2, Vec2};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+// random transforms tried on every generated stroke
+const CASES: usize = 8;
+const SMALL_ROTATION: f32 = 10.0 * PI / 180.0;
+// rubine tells gestures apart by which way they face (a check turned far enough is a v), so it gets less
+const RUBINE_ROTATION: f32 = 5.0 * PI / 180.0;
+
+fn transformed(stroke: &[StrokePoint], transform: impl Fn(Vec2) -> Vec2) -> Vec<StrokePoint> {
+    stroke
+        .iter()
+        .map(|point| StrokePoint {
+            pos: transform(point.pos),
+            ..*point
+        })
+        .collect()
+}
+
+// the name must stay the same and the score close to it, `tolerance` relative to the stroke's own score
+fn assert_invariant(name: &str, transform: impl Fn(&mut StdRng, Backend) -> Box<dyn Fn(Vec2) -> Vec2>, tolerance: Option<f32>) {
+    let mut rng = StdRng::seed_from_u64(50);
+    for recognizer in common::recognizers() {
+        for (label, stroke) in common::generated_strokes() {
+            let original = recognizer.recognize(&stroke).expect("there are templates to match");
+            for _ in 0..CASES {
+                let moved = recognizer
+                    .recognize(&transformed(&stroke, transform(&mut rng, recognizer.backend())))
+                    .expect("there are templates to match");
+                assert_eq!(
+                    moved.name,
+                    original.name,
+                    "a {} changed under {} with {:?}",
+                    label,
+                    name,
+                    recognizer.backend()
+                );
+                if let Some(tolerance) = tolerance {
+                    assert!(
+                        (moved.score - original.score).abs() <= tolerance * original.score.max(0.1),
+                        "a {} scored {} after {}, {} before with {:?}",
+                        label,
+                        moved.score,
+                        name,
+                        original.score,
+                        recognizer.backend()
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn translation() {
+    assert_invariant(
+        "translation",
+        |rng, _| {
+            let offset = Vec2::new(rng.gen_range(-2000.0..2000.0), rng.gen_range(-2000.0..2000.0));
+            Box::new(move |point| point + offset)
+        },
+        Some(0.01),
+    );
+}
+
+#[test]
+fn uniform_scale() {
+    assert_invariant(
+        "scaling",
+        |rng, _| {
+            let factor = rng.gen_range(0.1..10.0);
+            Box::new(move |point| point * factor)
+        },
+        Some(0.01),
+    );
+}
+
+// turned around the window's corner, so it moves as well; the score may shift a little,
+// the best angle is only searched for to within a couple of degrees
+#[test]
+fn small_rotation() {
+    assert_invariant(
+        "rotation",
+        |rng, backend| {
+            let limit = if backend == Backend::Rubine { RUBINE_ROTATION } else { SMALL_ROTATION };
+            let rotation = Mat2::from_angle(rng.gen_range(-limit..limit));
+            Box::new(move |point| rotation * point)
+        },
+        None,
+    );
+}
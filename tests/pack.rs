@@ -0,0 +1,43 @@
+// template packs have to come back exactly as they were saved, strokes and speed ranges included
+
+use dollar1_unistroke::pack::{from_json, to_json};
+use dollar1_unistroke::recognizer::{normalize, to_template};
+use dollar1_unistroke::stroke::StrokePoint;
+use dollar1_unistroke::templates::stroke_templates;
+use dollar1_unistroke::temporal::SpeedRange;
+use glam::Vec2;
+
+#[test]
+fn packs_round_trip() {
+    let mut templates = stroke_templates();
+    let stroke: Vec<StrokePoint> = (0..40)
+        .map(|i| {
+            let mut point = StrokePoint::new(Vec2::new(i as f32 * 5.0, (i as f32 * 0.3).sin() * 40.0), i as f32 * 0.01);
+            point.pressure = 0.5;
+            point.tilt = Some(0.2);
+            point
+        })
+        .collect();
+    let points: Vec<Vec2> = stroke.iter().map(|point| point.pos).collect();
+    let mut template = to_template(&normalize(&points)).expect("the stroke has length");
+    template.stroke = stroke.clone();
+    template.speed = Some(SpeedRange { min: 100.0, max: 900.0 });
+    templates.entry("wave".to_string()).or_default().insert(template);
+
+    let json = to_json(&templates);
+    let loaded = from_json(&json).expect("a saved pack loads");
+    assert_eq!(loaded, templates);
+
+    let wave = loaded["wave"].iter().next().unwrap();
+    assert_eq!(wave.stroke, stroke);
+    assert_eq!(wave.speed, Some(SpeedRange { min: 100.0, max: 900.0 }));
+
+    // the same templates always write the same file
+    assert_eq!(to_json(&loaded), json);
+}
+
+#[test]
+fn strokes_without_length_are_not_templates() {
+    let points = vec![Vec2::new(10.0, 10.0); 5];
+    assert!(to_template(&normalize(&points)).is_none());
+}
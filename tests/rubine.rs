@@ -0,0 +1,58 @@
+// rubine has to turn down what isn't a gesture and keep to the templates' speed ranges, like the nearest template backends,
+// and unlike them it minds which way a gesture faces
+
+mod common;
+
+use dollar1_unistroke::engine::Recognizer;
+use dollar1_unistroke::recognizer::Backend;
+use dollar1_unistroke::stroke::StrokePoint;
+use dollar1_unistroke::templates::stroke_templates;
+use dollar1_unistroke::temporal::SpeedRange;
+use glam::Vec2;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+#[test]
+fn scribbles_are_rejected() {
+    let recognizer = Recognizer::new(stroke_templates(), Backend::Rubine);
+    let mut rng = StdRng::seed_from_u64(37);
+    for _ in 0..20 {
+        let mut position = Vec2::new(300.0, 300.0);
+        let scribble: Vec<StrokePoint> = (0..40)
+            .map(|i| {
+                position += Vec2::new(rng.gen_range(-30.0..30.0), rng.gen_range(-30.0..30.0));
+                StrokePoint::new(position, i as f32 * 0.01)
+            })
+            .collect();
+        let recognition = recognizer.recognize(&scribble).expect("there are templates");
+        assert!(!recognition.recognized, "a scribble was taken for {} at {}", recognition.name, recognition.score);
+    }
+}
+
+#[test]
+fn speed_ranges_apply() {
+    let (_, v) = common::generated_strokes().into_iter().find(|(name, _)| name == "V").expect("there is a generated V");
+    assert_eq!(Recognizer::new(stroke_templates(), Backend::Rubine).recognize(&v).unwrap().name, "V");
+
+    // only for strokes drawn far faster than any hand
+    let mut templates = stroke_templates();
+    let set = templates.get_mut("V").unwrap();
+    *set = set
+        .drain()
+        .map(|mut template| {
+            template.speed = Some(SpeedRange { min: 100_000.0, max: 200_000.0 });
+            template
+        })
+        .collect();
+    let recognition = Recognizer::new(templates, Backend::Rubine).recognize(&v).unwrap();
+    assert_ne!(recognition.name, "V");
+}
+
+#[test]
+fn upside_down_is_not_the_gesture() {
+    let recognizer = Recognizer::new(stroke_templates(), Backend::Rubine);
+    for (label, stroke) in common::generated_strokes() {
+        let turned: Vec<StrokePoint> = stroke.iter().map(|point| StrokePoint { pos: -point.pos, ..*point }).collect();
+        let recognition = recognizer.recognize(&turned).expect("there are templates");
+        assert!(!recognition.recognized, "an upside down {} was taken for {}", label, recognition.name);
+    }
+}
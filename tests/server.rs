@@ -0,0 +1,123 @@
+// the recognition server over a real socket, the way the web and mobile clients talk to it
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+
+use dollar1_unistroke::engine::Recognizer;
+use dollar1_unistroke::server::RecognitionService;
+use dollar1_unistroke::templates::stroke_templates;
+
+const PAGE: &str = "http://localhost:8080";
+
+fn start() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("a free port on loopback");
+    let addr = listener.local_addr().unwrap();
+    // left running until the test process ends
+    thread::spawn(move || {
+        RecognitionService::new(Recognizer::default(), None)
+            .with_origins(vec![PAGE.to_string()])
+            .serve(&listener)
+    });
+    addr
+}
+
+// (status, body), sent as json the way the clients do
+fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+    let (status, _, body) = request_with(addr, method, path, "Content-Type: application/json\r\n", body);
+    (status, body)
+}
+
+// (status, headers, body), with `headers` ending in \r\n each
+fn request_with(addr: SocketAddr, method: &str, path: &str, headers: &str, body: &str) -> (u16, String, String) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "{} {} HTTP/1.1\r\nHost: {}\r\n{}Content-Length: {}\r\n\r\n{}", method, path, addr, headers, body.len(), body)
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let status = response.split_whitespace().nth(1).and_then(|status| status.parse().ok()).expect("a status line");
+    let (headers, body) = response.split_once("\r\n\r\n").expect("headers end");
+    (status, headers.to_string(), body.to_string())
+}
+
+// a template of the built-in gesture as {"name", "points"}
+fn stroke_json(name: &str, gesture: &str) -> String {
+    let set = &stroke_templates()[gesture];
+    let template = set.iter().next().unwrap();
+    let points: Vec<String> = template.points.iter().map(|point| format!("[{}, {}]", point.x, point.y)).collect();
+    format!("{{\"name\": \"{}\", \"points\": [{}]}}", name, points.join(", "))
+}
+
+#[test]
+fn recognizes_and_adds_templates_over_loopback() {
+    let addr = start();
+
+    let (status, body) = request(addr, "POST", "/recognize", &stroke_json("anything", "Star"));
+    assert_eq!(status, 200, "{}", body);
+    let response: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(response["recognized"], true);
+    assert_eq!(response["name"], "Star");
+
+    let (status, body) = request(addr, "POST", "/templates", &stroke_json("zorro", "Pigtail"));
+    assert_eq!(status, 200, "{}", body);
+    let response: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(response["name"], "zorro");
+    assert_eq!(response["templates"], 1);
+
+    let (status, body) = request(addr, "GET", "/templates", "");
+    assert_eq!(status, 200);
+    assert!(body.contains("\"zorro\""), "the added template is in the pack");
+
+    let (status, _) = request(addr, "POST", "/recognize", "not a stroke");
+    assert_eq!(status, 400);
+    let (status, _) = request(addr, "GET", "/nowhere", "");
+    assert_eq!(status, 404);
+}
+
+#[test]
+fn a_silent_client_does_not_hold_up_the_others() {
+    let addr = start();
+    let _silent = TcpStream::connect(addr).unwrap();
+
+    let (status, body) = request(addr, "POST", "/recognize", &stroke_json("anything", "Circle"));
+    assert_eq!(status, 200, "{}", body);
+}
+
+#[test]
+fn only_allowed_pages_get_in() {
+    let addr = start();
+    let star = stroke_json("anything", "Star");
+
+    let (status, headers, body) =
+        request_with(addr, "POST", "/recognize", &format!("Origin: {}\r\nContent-Type: application/json\r\n", PAGE), &star);
+    assert_eq!(status, 200, "{}", body);
+    assert!(headers.contains(&format!("Access-Control-Allow-Origin: {}", PAGE)), "{}", headers);
+
+    let other = "Origin: http://example.com\r\nContent-Type: application/json\r\n";
+    let (status, headers, _) = request_with(addr, "POST", "/templates", other, &stroke_json("zorro", "Pigtail"));
+    assert_eq!(status, 403);
+    assert!(!headers.contains("Access-Control-Allow-Origin"), "{}", headers);
+    let (status, _, _) = request_with(addr, "OPTIONS", "/templates", other, "");
+    assert_eq!(status, 403);
+
+    // and nothing else gets told any page may read the answers
+    let (_, headers, _) = request_with(addr, "GET", "/templates", "", "");
+    assert!(!headers.contains("Access-Control-Allow-Origin"), "{}", headers);
+}
+
+#[test]
+fn posts_have_to_be_json() {
+    let addr = start();
+    let pigtail = stroke_json("zorro", "Pigtail");
+
+    // what a form on another page could send without asking first
+    let (status, _, _) = request_with(addr, "POST", "/templates", "Content-Type: text/plain\r\n", &pigtail);
+    assert_eq!(status, 415);
+    let (status, _, _) = request_with(addr, "POST", "/templates", "", &pigtail);
+    assert_eq!(status, 415);
+
+    let (status, _, body) =
+        request_with(addr, "POST", "/templates", "Content-Type: application/json; charset=utf-8\r\n", &pigtail);
+    assert_eq!(status, 200, "{}", body);
+}
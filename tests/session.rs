@@ -0,0 +1,75 @@
+// a recorded session has to replay to what was recorded, through the file it's written to
+
+mod common;
+
+use dollar1_unistroke::recognizer::REJECT_DISTANCE;
+use dollar1_unistroke::session::{read_session, replay, Outcome, RecordedStroke, SessionEvent, SessionWriter};
+use dollar1_unistroke::stroke::StrokePoint;
+use dollar1_unistroke::templates::stroke_templates;
+use glam::Vec2;
+
+#[test]
+fn recorded_strokes_replay_the_same() {
+    let mut events = vec![SessionEvent::Templates {
+        at: 0.0,
+        templates: stroke_templates(),
+    }];
+    let recognizers = common::recognizers();
+    // every backend gets a turn
+    for (recognizer, (at, (_, stroke))) in recognizers.iter().cycle().zip(common::generated_strokes().into_iter().enumerate()) {
+        let recognition = recognizer.recognize(&stroke).expect("there are templates");
+        events.push(SessionEvent::Stroke(RecordedStroke {
+            at: at as f32,
+            backend: recognizer.backend(),
+            reject_distance: REJECT_DISTANCE,
+            stroke,
+            outcome: Outcome::new(Some((&recognition.name, recognition.score)), REJECT_DISTANCE),
+        }));
+    }
+
+    let mut file = Vec::new();
+    let mut writer = SessionWriter::new(&mut file);
+    for event in events.iter() {
+        writer.write(event).unwrap();
+    }
+    let read = read_session(&String::from_utf8(file).unwrap()).expect("a written session reads back");
+    assert_eq!(read.len(), events.len());
+
+    let replayed = replay(&read);
+    assert_eq!(replayed.len(), events.len() - 1);
+    for stroke in replayed.iter() {
+        assert!(stroke.matches(), "recorded as {}, replayed as {}", stroke.recorded, stroke.replayed);
+    }
+}
+
+// a click has no shape, it's recorded as nothing and has to replay as nothing, with every backend
+#[test]
+fn clicks_replay_as_no_stroke() {
+    let click = vec![StrokePoint::new(Vec2::new(120.0, 80.0), 0.0), StrokePoint::new(Vec2::new(120.0, 80.0), 0.05)];
+    let mut file = Vec::new();
+    let mut writer = SessionWriter::new(&mut file);
+    for recognizer in common::recognizers() {
+        assert!(recognizer.recognize(&click).is_none(), "a click was recognized with {:?}", recognizer.backend());
+        let event = SessionEvent::Stroke(RecordedStroke {
+            at: 0.0,
+            backend: recognizer.backend(),
+            reject_distance: REJECT_DISTANCE,
+            stroke: click.clone(),
+            outcome: Outcome::new(None, REJECT_DISTANCE),
+        });
+        writer.write(&event).unwrap();
+    }
+    let read = read_session(&String::from_utf8(file).unwrap()).expect("a written session reads back");
+    for stroke in replay(&read).iter() {
+        assert!(stroke.matches(), "a click recorded as {}, replayed as {}", stroke.recorded, stroke.replayed);
+    }
+}
+
+#[test]
+fn outcomes_match_within_a_tolerance() {
+    let recorded = Outcome::new(Some(("Star", 12.5)), REJECT_DISTANCE);
+    assert!(recorded.matches(&Outcome::new(Some(("Star", 12.500_01)), REJECT_DISTANCE)));
+    assert!(!recorded.matches(&Outcome::new(Some(("Star", 13.0)), REJECT_DISTANCE)));
+    assert!(!recorded.matches(&Outcome::new(Some(("Pigtail", 12.5)), REJECT_DISTANCE)));
+    assert!(!recorded.matches(&Outcome::new(None, REJECT_DISTANCE)));
+}
@@ -0,0 +1,19 @@
+// guessing while the stroke is drawn: three quarters of a gesture already give it away
+
+use dollar1_unistroke::streaming::PrefixIndex;
+use dollar1_unistroke::templates::stroke_templates;
+
+#[test]
+fn partial_templates_are_guessed_as_themselves() {
+    let templates = stroke_templates();
+    let index = PrefixIndex::new(&templates);
+    for (name, set) in templates.iter() {
+        for template in set.iter() {
+            let partial = &template.points[..template.points.len() * 3 / 4];
+            let guess = index.guess(partial).expect("there are templates to guess from");
+            assert_eq!(&guess.name, name);
+            assert!((guess.progress - 0.75).abs() <= 0.125, "{} guessed {} along", name, guess.progress);
+            assert!(!guess.completion.is_empty(), "the rest of {} is shown", name);
+        }
+    }
+}